@@ -1,7 +1,53 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::native_token::LAMPORTS_PER_SOL};
 
+//  bootstraps the authority stored in the config, afterwards the stored authority is used
 pub const ADMIN_ADDRESS: Pubkey = pubkey!("AMeGg9qpzv1geQpiEWzhgXempJTuYYZeuLLKX1cYbmaw");
 
 pub const TOKEN_DECIMAL: u8 = 6;
 
-pub const CONFIG_SEED_IN_BYTES: &[u8; 13] = b"global-config";
\ No newline at end of file
+//  current account layouts, fields added later take their bytes from `reserved` and keep the version
+pub const CONFIG_VERSION: u8 = 1;
+pub const BONDING_CURVE_VERSION: u8 = 1;
+//  accounts created before the version byte, recognized by their length
+pub const LEGACY_LAYOUT_VERSION: u8 = 0;
+
+pub const CONFIG_SEED_IN_BYTES: &[u8; 13] = b"global-config";
+
+pub const POOL_SEED_IN_BYTES: &[u8; 4] = b"pool";
+
+pub const LP_MINT_SEED_IN_BYTES: &[u8; 7] = b"lp-mint";
+
+pub const VESTING_SEED_IN_BYTES: &[u8; 7] = b"vesting";
+
+pub const REFERRER_SEED_IN_BYTES: &[u8; 8] = b"referrer";
+
+pub const TRADER_STATS_SEED_IN_BYTES: &[u8; 12] = b"trader-stats";
+
+pub const PRICE_ORACLE_SEED_IN_BYTES: &[u8; 12] = b"price-oracle";
+
+//  signs the self-CPIs that carry events, must match the seed `#[event_cpi]` derives
+pub const EVENT_AUTHORITY_SEED_IN_BYTES: &[u8; 17] = b"__event_authority";
+
+//  longest vesting schedule a launch can set, keeps the vesting timestamps far from overflowing
+pub const MAX_VESTING_DURATION_SECONDS: i64 = 10 * 365 * 24 * 60 * 60;
+
+//  initial virtual lamport reserves of curves launched before they became configurable
+pub const DEFAULT_VIRTUAL_LAMPORT_RESERVES: u64 = 100 * LAMPORTS_PER_SOL;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+//  spot prices are quoted in lamports per token unit scaled by this factor
+pub const PRICE_PRECISION: u128 = 1_000_000_000;
+
+//  swap fee of graduated pools, kept in the pool for liquidity providers
+pub const POOL_FEE_BPS: u16 = 30;
+
+//  fee shares paid out next to the fee recipient on every curve trade
+pub const MAX_FEE_SHARES: usize = 4;
+
+//  volume based discounts on the buy and sell fees
+pub const MAX_FEE_TIERS: usize = 4;
+
+//  price history kept per curve for the TWAP, swaps write at most one observation per interval
+pub const MAX_PRICE_OBSERVATIONS: usize = 64;
+pub const PRICE_OBSERVATION_INTERVAL_SECONDS: i64 = 60;
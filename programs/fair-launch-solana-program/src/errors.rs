@@ -19,4 +19,79 @@ pub enum CustomError {
 
     #[msg("Curve is already completed")]
     CurveAlreadyCompleted,
+
+    #[msg("Amount in is larger than allowed amount")]
+    AmountInTooLarge,
+
+    #[msg("Amount out is larger than what is left before the curve completes")]
+    AmountOutTooLarge,
+
+    #[msg("Curve is not completed yet")]
+    CurveNotCompleted,
+
+    #[msg("Curve has already been migrated")]
+    CurveAlreadyMigrated,
+
+    #[msg("Pool has no liquidity")]
+    PoolEmpty,
+
+    #[msg("Withdraw destination address is not match with the one in the config")]
+    IncorrectWithdrawDestination,
+
+    #[msg("Curve has expired, only refunds are allowed")]
+    CurveExpired,
+
+    #[msg("Curve has not expired")]
+    CurveNotExpired,
+
+    #[msg("Vesting accounts do not match the launch options")]
+    IncorrectVestingAccounts,
+
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+
+    #[msg("Metadata accounts do not match the token program")]
+    IncorrectMetadataAccounts,
+
+    #[msg("Program is paused")]
+    ProgramPaused,
+
+    #[msg("Trading is paused on this curve")]
+    TradingPaused,
+
+    #[msg("Traders cannot refer themselves")]
+    IncorrectReferrer,
+
+    #[msg("Not enough price history for the requested window")]
+    NotEnoughPriceHistory,
+
+    #[msg("Curve has no liquidity left")]
+    CurveHasNoLiquidity,
+
+    #[msg("Curve is no longer trading, it has no live price")]
+    CurveNotTrading,
+
+    #[msg("Fee share recipient must already be rent exempt")]
+    FeeRecipientNotRentExempt,
+
+    #[msg("Migrated curves are priced by their pool, which must be passed")]
+    PoolRequired,
+
+    #[msg("Initial buy accounts do not match the launch options")]
+    IncorrectInitialBuyAccounts,
+
+    #[msg("Curve has already been withdrawn")]
+    CurveAlreadyWithdrawn,
+
+    #[msg("Trader is already bound to another referrer")]
+    ReferrerMismatch,
+
+    #[msg("Account is already on the current layout")]
+    AccountAlreadyMigrated,
+
+    #[msg("Account layout version is not supported")]
+    UnsupportedLayoutVersion,
+
+    #[msg("Launches with a reserved allocation must set an expiry")]
+    VestingRequiresExpiry,
 }
@@ -1,18 +1,193 @@
 use anchor_lang::{*, prelude::*};
+use crate::states::{CurveParams, FeeShare, FeeTier};
 
 #[event]
 pub struct TradeEvent {
     pub trader: Pubkey,
     pub asset: Pubkey,
     pub is_buy: bool,
-    pub amount_in: u64,
-    pub amount_out: u64,
+    pub amount_in: u64, //  lamports paid on buys, fees included
+    pub amount_out: u64, //  what the trader received, sells are net of the fee like `SwapQuote::amount_out`
+    pub referrer: Option<Pubkey>, //  wallet of the referrer, if the trade was referred
+    pub referral_fee_lamports: u64,
+    pub fee_lamports: u64, //  whole fee of the trade, creator and referral fees included
+    pub creator_fee_lamports: u64,
+    //  reserves of the curve after the trade
+    pub virtual_lamport_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub actual_lamport_reserves: u64,
+    pub spot_price: u64, //  lamports per token unit after the trade, scaled by `PRICE_PRECISION`
+    pub sequence: u64, //  per curve, increases by one with every trade
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[event]
 pub struct LaunchEvent {
+    pub creator: Pubkey,
     pub mint: Pubkey,
     pub name: String,
     pub symbol: String,
     pub uri: String,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct CurveCompletedEvent {
+    pub mint: Pubkey,
+    pub virtual_token_reserves: u64,
+    pub virtual_lamport_reserves: u64,
+    pub actual_lamport_reserves: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct CurveMigratedEvent {
+    pub mint: Pubkey,
+    pub pool: Pubkey,
+    pub lamport_reserves: u64,
+    pub token_reserves: u64,
+    pub tokens_burned: u64, //  curve tokens above the final curve price, burned instead of seeding the pool
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct PoolTradeEvent {
+    pub trader: Pubkey,
+    pub asset: Pubkey,
+    pub is_buy: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+#[event]
+pub struct PoolLiquidityEvent {
+    pub provider: Pubkey,
+    pub asset: Pubkey,
+    pub is_deposit: bool,
+    pub lamports: u64,
+    pub tokens: u64,
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct CurveWithdrawnEvent {
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    pub tokens: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RefundClaimedEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub tokens_burned: u64,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct ProgramPausedEvent {
+    pub paused: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct CurvePausedEvent {
+    pub mint: Pubkey,
+    pub paused: bool,
+    pub slot: u64,
+}
+
+//  fields changed by one of the config update instructions, with their values before and after the update
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ConfigUpdate {
+    Fees {
+        old_buy_fee_bps: u16,
+        new_buy_fee_bps: u16,
+        old_sell_fee_bps: u16,
+        new_sell_fee_bps: u16,
+        old_creator_fee_bps: u16,
+        new_creator_fee_bps: u16,
+        old_referral_fee_bps: u16,
+        new_referral_fee_bps: u16,
+        old_launch_fee_lamports: u64,
+        new_launch_fee_lamports: u64,
+    },
+    FeeRecipient {
+        old_fee_recipient: Pubkey,
+        new_fee_recipient: Pubkey,
+        old_withdraw_destination: Pubkey,
+        new_withdraw_destination: Pubkey,
+    },
+    CurveParams {
+        old_curve_params: CurveParams,
+        new_curve_params: CurveParams,
+    },
+    LaunchAccess {
+        old_admin_only_launch: bool,
+        new_admin_only_launch: bool,
+    },
+    FeeShares {
+        old_fee_shares: Vec<FeeShare>,
+        new_fee_shares: Vec<FeeShare>,
+    },
+    FeeTiers {
+        old_fee_tiers: Vec<FeeTier>,
+        new_fee_tiers: Vec<FeeTier>,
+    },
+}
+
+#[event]
+pub struct ConfigUpdatedEvent {
+    pub authority: Pubkey,
+    pub update: ConfigUpdate,
+}
+
+#[event]
+pub struct AuthorityProposedEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferredEvent {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct CreatorFeeAccruedEvent {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub lamports: u64,
+    pub accrued_lamports: u64,
+}
+
+#[event]
+pub struct CreatorFeesClaimedEvent {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct ReferralFeesClaimedEvent {
+    pub referrer: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct VestingClaimedEvent {
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub claimed_amount: u64,
+}
+
+#[event]
+pub struct ReservedTokensBurnedEvent {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
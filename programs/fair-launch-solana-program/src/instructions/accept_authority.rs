@@ -0,0 +1,35 @@
+use crate::{errors::CustomError, events::AuthorityTransferredEvent, states::Config};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        constraint = global_config.pending_authority == new_authority.key() @CustomError::NotAuthorized
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> AcceptAuthority<'info> {
+    pub fn handle(&mut self, bump_event_authority: u8) -> Result<()> {
+        let global_config = &mut self.global_config;
+        let previous_authority = global_config.authority;
+
+        global_config.authority = self.new_authority.key();
+        global_config.pending_authority = Pubkey::default();
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &AuthorityTransferredEvent {
+            previous_authority,
+            authority: global_config.authority,
+        })?;
+
+        Ok(())
+    }
+}
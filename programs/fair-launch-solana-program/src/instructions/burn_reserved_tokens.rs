@@ -0,0 +1,76 @@
+use crate::{constants, errors::CustomError, events::ReservedTokensBurnedEvent, states::{BondingCurve, Vesting}};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use constants::VESTING_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BurnReservedTokens<'info> {
+    #[account(mut)]
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+    #[account(
+        mut,
+        seeds = [VESTING_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = vesting.bump,
+    )]
+    vesting: Box<Account<'info, Vesting>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    vesting_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> BurnReservedTokens<'info> {
+    //  permissionless, a curve that expired never unlocks its reserved allocation so it is burned instead
+    pub fn handle(&mut self, bump_event_authority: u8) -> Result<()> {
+        let bonding_curve = &mut self.bonding_curve;
+
+        require!(
+            bonding_curve.is_expired(Clock::get()?.unix_timestamp),
+            CustomError::CurveNotExpired
+        );
+
+        let amount = self.vesting_token_account.amount;
+        require!(amount > 0, CustomError::IncorrectValue);
+
+        let vesting = &mut self.vesting;
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token_interface::Burn {
+                    mint: self.token_mint.to_account_info(),
+                    from: self.vesting_token_account.to_account_info(),
+                    authority: vesting.to_account_info(),
+                },
+                &[&Vesting::get_signer(&self.token_mint.key(), &vesting.bump)],
+            ),
+            amount,
+        )?;
+
+        //  the burned tokens leave the supply, refunds keep excluding only what is still reserved
+        bonding_curve.reserved_tokens = bonding_curve
+            .reserved_tokens
+            .checked_sub(amount)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        vesting.total_amount = vesting.claimed_amount;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ReservedTokensBurnedEvent {
+            mint: self.token_mint.key(),
+            amount,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{errors::CustomError, events::CreatorFeesClaimedEvent, states::BondingCurve};
+use crate::utils::{emit_cpi_event, sol_transfer_from_pda};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimCreatorFees<'info> {
+    #[account(mut)]
+    creator: Signer<'info>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [&token_mint.key().to_bytes()],
+        bump,
+        has_one = creator @CustomError::NotAuthorized,
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+}
+
+impl<'info> ClaimCreatorFees<'info> {
+    //  creator fees are kept apart from the reserves, so they can be claimed at any stage of the curve
+    pub fn handle(&mut self, bump_event_authority: u8) -> Result<()> {
+        let bonding_curve = &mut self.bonding_curve;
+
+        let lamports = bonding_curve.creator_fee_lamports;
+        require!(lamports > 0, CustomError::NothingToClaim);
+
+        sol_transfer_from_pda(
+            &mut bonding_curve.to_account_info(),
+            &mut self.creator.to_account_info(),
+            lamports,
+        )?;
+        bonding_curve.creator_fee_lamports = 0;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &CreatorFeesClaimedEvent {
+            mint: self.token_mint.key(),
+            creator: self.creator.key(),
+            lamports,
+        })?;
+
+        Ok(())
+    }
+}
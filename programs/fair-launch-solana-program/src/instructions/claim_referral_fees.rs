@@ -0,0 +1,42 @@
+use crate::{constants, errors::CustomError, events::ReferralFeesClaimedEvent, states::Referrer};
+use crate::utils::{emit_cpi_event, sol_transfer_from_pda};
+use anchor_lang::prelude::*;
+use constants::REFERRER_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(mut)]
+    wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [REFERRER_SEED_IN_BYTES, wallet.key().as_ref()],
+        bump = referrer.bump,
+        has_one = wallet @CustomError::NotAuthorized,
+    )]
+    referrer: Box<Account<'info, Referrer>>,
+}
+
+impl<'info> ClaimReferralFees<'info> {
+    pub fn handle(&mut self, bump_event_authority: u8) -> Result<()> {
+        let referrer = &mut self.referrer;
+
+        let lamports = referrer.accrued_lamports;
+        require!(lamports > 0, CustomError::NothingToClaim);
+
+        sol_transfer_from_pda(
+            &mut referrer.to_account_info(),
+            &mut self.wallet.to_account_info(),
+            lamports,
+        )?;
+        referrer.accrued_lamports = 0;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ReferralFeesClaimedEvent {
+            referrer: self.wallet.key(),
+            lamports,
+        })?;
+
+        Ok(())
+    }
+}
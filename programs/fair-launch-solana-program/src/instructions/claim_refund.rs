@@ -0,0 +1,107 @@
+use crate::{constants, errors::CustomError, events::RefundClaimedEvent, states::{BondingCurve, PriceOracle}};
+use crate::utils::{emit_cpi_event, sol_transfer_from_pda};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use constants::PRICE_ORACLE_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    user: Signer<'info>,
+
+    #[account(mut)]
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+    #[account(
+        mut,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    #[account(
+        associated_token::mint = token_mint,
+        associated_token::authority = bonding_curve,
+        associated_token::token_program = token_program
+    )]
+    curve_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClaimRefund<'info> {
+    pub fn handle(&mut self, bump_event_authority: u8) -> Result<()> {
+        let bonding_curve = &mut self.bonding_curve;
+
+        require!(
+            bonding_curve.is_expired(Clock::get()?.unix_timestamp),
+            CustomError::CurveNotExpired
+        );
+
+        let tokens_burned = self.user_token_account.amount;
+        require!(tokens_burned > 0, CustomError::IncorrectValue);
+
+        //  every token bought from the curve has a claim on the actual reserves
+        let circulating_tokens = self
+            .token_mint
+            .supply
+            .checked_sub(self.curve_token_account.amount)
+            .and_then(|tokens| tokens.checked_sub(bonding_curve.reserved_tokens))
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let lamports = bonding_curve.calc_refund(tokens_burned, circulating_tokens)?;
+
+        token_interface::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token_interface::Burn {
+                    mint: self.token_mint.to_account_info(),
+                    from: self.user_token_account.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            tokens_burned,
+        )?;
+        sol_transfer_from_pda(
+            &mut bonding_curve.to_account_info(),
+            &mut self.user.to_account_info(),
+            lamports,
+        )?;
+
+        //  virtual lamports move with the actual ones so the virtual offset is preserved
+        let new_virtual_lamport_reserves = bonding_curve
+            .virtual_lamport_reserves
+            .checked_sub(lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let new_actual_lamport_reserves = bonding_curve
+            .actual_lamport_reserves
+            .checked_sub(lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let virtual_token_reserves = bonding_curve.virtual_token_reserves;
+        self.price_oracle.update_price_cumulative(
+            PriceOracle::calc_spot_price(bonding_curve, None)?,
+            Clock::get()?.unix_timestamp,
+        );
+        bonding_curve.update_reserves(new_virtual_lamport_reserves, virtual_token_reserves, new_actual_lamport_reserves);
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &RefundClaimedEvent {
+            user: self.user.key(),
+            mint: self.token_mint.key(),
+            tokens_burned,
+            lamports,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{constants, errors::CustomError, events::VestingClaimedEvent, states::{BondingCurve, Vesting}};
+use crate::utils::{emit_cpi_event, token_transfer_from_pda};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use constants::VESTING_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    beneficiary: Signer<'info>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+    #[account(
+        mut,
+        seeds = [VESTING_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = vesting.bump,
+        has_one = beneficiary @CustomError::NotAuthorized,
+    )]
+    vesting: Box<Account<'info, Vesting>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    vesting_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = token_mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program
+    )]
+    beneficiary_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+    #[account(address = associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimVested<'info> {
+    pub fn handle(&mut self, bump_event_authority: u8) -> Result<()> {
+        //  reserved tokens stay locked until the curve graduates, so they can never be refunded or dumped on it,
+        //  if the curve expires instead they are burned through `burn_reserved_tokens`, launches with a reserved
+        //  allocation always expire so one of the two eventually happens
+        require!(
+            self.bonding_curve.is_completed,
+            CustomError::CurveNotCompleted
+        );
+
+        let vesting = &mut self.vesting;
+        let amount = vesting
+            .calc_unlocked_amount(Clock::get()?.unix_timestamp)?
+            .checked_sub(vesting.claimed_amount)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        require!(amount > 0, CustomError::NothingToClaim);
+
+        token_transfer_from_pda(
+            &self.vesting_token_account.to_account_info(),
+            &vesting.to_account_info(),
+            &self.beneficiary_token_account.to_account_info(),
+            &self.token_mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            &[&Vesting::get_signer(&self.token_mint.key(), &vesting.bump)],
+            amount,
+            self.token_mint.decimals,
+        )?;
+
+        vesting.claimed_amount = vesting
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &VestingClaimedEvent {
+            beneficiary: self.beneficiary.key(),
+            mint: self.token_mint.key(),
+            amount,
+            claimed_amount: vesting.claimed_amount,
+        })?;
+
+        Ok(())
+    }
+}
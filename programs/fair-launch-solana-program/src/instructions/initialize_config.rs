@@ -0,0 +1,50 @@
+use crate::{errors::CustomError, states::{Config, CurveParams}};
+use anchor_lang::prelude::*;
+use crate::constants::{ADMIN_ADDRESS, CONFIG_SEED_IN_BYTES, CONFIG_VERSION};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut, address = ADMIN_ADDRESS @CustomError::NotAuthorized)]
+    admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        space = 8 + Config::INIT_SPACE,
+        bump,
+    )]
+    global_config: Account<'info, Config>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle(
+        &mut self,
+        fee_recipient: Pubkey,
+        withdraw_destination: Pubkey,
+        buy_fee_bps: u16,
+        sell_fee_bps: u16,
+        launch_fee_lamports: u64,
+        admin_only_launch: bool,
+        curve_params: CurveParams,
+    ) -> Result<()> {
+        let global_config = &mut self.global_config;
+
+        global_config.version = CONFIG_VERSION;
+        //  the deploy-time admin becomes the first authority
+        global_config.authority = self.admin.key();
+        global_config.pending_authority = Pubkey::default();
+        global_config.fee_recipient = fee_recipient;
+        global_config.withdraw_destination = withdraw_destination;
+        global_config.buy_fee_bps = buy_fee_bps;
+        global_config.sell_fee_bps = sell_fee_bps;
+        global_config.launch_fee_lamports = launch_fee_lamports;
+        global_config.admin_only_launch = admin_only_launch;
+        global_config.set_curve_params(curve_params);
+        global_config.paused = false;
+
+        global_config.validate()
+    }
+}
@@ -1,60 +1,100 @@
 use anchor_lang::{prelude::*, system_program, solana_program::sysvar::SysvarId};
-use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
-use crate::constants::{ADMIN_ADDRESS, TOKEN_DECIMAL, CONFIG_SEED_IN_BYTES};
-use crate::states::{Config, BondingCurve};
+use crate::constants::{BONDING_CURVE_VERSION, TOKEN_DECIMAL, CONFIG_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES, TRADER_STATS_SEED_IN_BYTES, VESTING_SEED_IN_BYTES};
+use crate::states::{Config, BondingCurve, PriceOracle, SwapAmount, TraderStats, Vesting, VestingParams};
 use anchor_spl::{
     associated_token::{self, AssociatedToken},
     metadata::{self, mpl_token_metadata::types::DataV2, Metadata},
-    token::{self, spl_token::instruction::AuthorityType, Mint, Token, TokenAccount},
+    token_interface::{
+        self,
+        spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_2022::{self, extension::ExtensionType, instruction::AuthorityType},
+        spl_token_metadata_interface::state::TokenMetadata,
+        TokenInterface,
+    },
 };
-use crate::events::LaunchEvent;
+use crate::errors::CustomError;
+use crate::events::{CurveCompletedEvent, LaunchEvent};
+use crate::utils::{emit_cpi_event, sol_transfer_from_user};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Launch<'info> {
-    #[account(mut, address = ADMIN_ADDRESS)]
-    admin: Signer<'info>,
+    #[account(mut)]
+    creator: Signer<'info>,
 
     #[account(
         seeds = [CONFIG_SEED_IN_BYTES],
         bump,
     )]
     global_config: Account<'info, Config>,
-
+    /// CHECK: should be same with the address in the global_config
     #[account(
-        init,
-        payer = admin,
-        mint::decimals = TOKEN_DECIMAL,
-        mint::authority = global_config.key(),
+        mut,
+        constraint = global_config.fee_recipient == fee_recipient.key() @CustomError::IncorrectFeeRecipient
     )]
-    token_mint: Box<Account<'info, Mint>>,
+    fee_recipient: AccountInfo<'info>,
+
+    //  fresh keypair, created in the handler under whichever token program is passed
+    #[account(mut)]
+    token_mint: Signer<'info>,
 
     #[account(
         init,
-        payer = admin,
-        space = 8 + std::mem::size_of::<BondingCurve>(),
+        payer = creator,
+        space = 8 + BondingCurve::INIT_SPACE,
         seeds = [token_mint.key().as_ref()],
         bump
     )]
     bonding_curve: Box<Account<'info, BondingCurve>>,
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PriceOracle::INIT_SPACE,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
 
+    /// CHECK: created in the handler through the associated token program
+    #[account(mut)]
+    curve_token_account: UncheckedAccount<'info>,
+
+    //  only needed when part of the supply is reserved for vesting
     #[account(
         init,
-        payer = admin,
-        associated_token::mint = token_mint,
-        associated_token::authority = bonding_curve
+        payer = creator,
+        space = 8 + Vesting::INIT_SPACE,
+        seeds = [VESTING_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump
+    )]
+    vesting: Option<Box<Account<'info, Vesting>>>,
+    /// CHECK: created in the handler through the associated token program
+    #[account(mut)]
+    vesting_token_account: Option<UncheckedAccount<'info>>,
+
+    //  only needed when the creator buys at launch
+    /// CHECK: created in the handler through the associated token program
+    #[account(mut)]
+    creator_token_account: Option<UncheckedAccount<'info>>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + TraderStats::INIT_SPACE,
+        seeds = [TRADER_STATS_SEED_IN_BYTES, creator.key().as_ref()],
+        bump
     )]
-    curve_token_account: Box<Account<'info, TokenAccount>>,
+    trader_stats: Option<Box<Account<'info, TraderStats>>>,
 
+    //  only needed for legacy SPL mints, token-2022 mints carry their metadata in the mint itself
     /// CHECK: initialized through CPI (token metadata program)
     #[account(mut)]
-    token_metadata_account: UncheckedAccount<'info>,
+    token_metadata_account: Option<UncheckedAccount<'info>>,
 
-    #[account(address = token::ID)]
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     #[account(address = associated_token::ID)]
     associated_token_program: Program<'info, AssociatedToken>,
     #[account(address = metadata::ID)]
-    metadata_program: Program<'info, Metadata>,
+    metadata_program: Option<Program<'info, Metadata>>,
     #[account(address = system_program::ID)]
     system_program: Program<'info, System>,
     #[account(address = Rent::id())]
@@ -62,6 +102,7 @@ pub struct Launch<'info> {
 }
 
 impl<'info> Launch<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn handle(
         &mut self,
 
@@ -70,67 +111,216 @@ impl<'info> Launch<'info> {
         symbol: String,
         uri: String,
 
+        //  optional refund deadline
+        expires_at: Option<i64>,
+
+        //  optional overrides of the configured initial virtual reserves
+        virtual_lamport_reserves: Option<u64>,
+        virtual_token_reserves: Option<u64>,
+
+        //  optional reserved allocation
+        vesting: Option<VestingParams>,
+
+        //  optional creator buy, executed in the same transaction
+        initial_buy_lamports: Option<u64>,
+        min_amount_out: u64,
+
         global_config_bump: u8,
+        bonding_curve_bump: u8,
+        vesting_bump: Option<u8>,
+        price_oracle_bump: u8,
+        trader_stats_bump: Option<u8>,
+        event_authority_bump: u8,
+        fee_share_accounts: &[AccountInfo<'info>],
     ) -> Result<()> {
         let bonding_curve = &mut self.bonding_curve;
         let global_config = &self.global_config;
 
+        require!(
+            !global_config.paused,
+            CustomError::ProgramPaused
+        );
+
+        //  launching can be restricted to the authority
+        if global_config.admin_only_launch {
+            require!(
+                self.creator.key() == global_config.authority,
+                CustomError::NotAuthorized
+            );
+        }
+
+        //  charge the launch fee
+        if global_config.launch_fee_lamports > 0 {
+            sol_transfer_from_user(
+                &self.creator,
+                &self.fee_recipient,
+                &self.system_program.to_account_info(),
+                global_config.launch_fee_lamports,
+            )?;
+        }
+
+        //  the reserved allocation comes out of both the minted and the virtual token reserves of the curve
+        let reserved_tokens = match &vesting {
+            Some(vesting) => {
+                global_config.validate_vesting_params(vesting)?;
+                vesting.calc_reserved_amount(global_config.total_token_supply)?
+            }
+            None => 0,
+        };
+        let curve_tokens = global_config.total_token_supply - reserved_tokens;
+
         // initialising bonding curve pda
-        bonding_curve.virtual_lamport_reserves = 100 * LAMPORTS_PER_SOL; // initial mcap
+        bonding_curve.version = BONDING_CURVE_VERSION;
+        bonding_curve.creator = self.creator.key();
+        let (virtual_lamport_reserves, virtual_token_reserves) =
+            global_config.resolve_virtual_reserves(virtual_lamport_reserves, virtual_token_reserves)?;
+        let virtual_token_reserves = virtual_token_reserves
+            .checked_sub(reserved_tokens)
+            .filter(|reserves| *reserves > 0)
+            .ok_or(CustomError::IncorrectValue)?;
+        bonding_curve.virtual_lamport_reserves = virtual_lamport_reserves; // initial mcap
         bonding_curve.is_completed = false;
-        // TODO: This might change to reserve tokens for influencer
-        bonding_curve.virtual_token_reserves = global_config.total_token_supply;
+        bonding_curve.expires_at = match expires_at {
+            Some(expires_at) => {
+                require!(
+                    expires_at > Clock::get()?.unix_timestamp,
+                    CustomError::IncorrectValue
+                );
+                expires_at
+            }
+            None => 0,
+        };
+        //  reserved tokens only leave the escrow once the curve completes or are burned once it expires,
+        //  a curve that does neither would lock them forever
+        require!(
+            reserved_tokens == 0 || bonding_curve.expires_at > 0,
+            CustomError::VestingRequiresExpiry
+        );
+        bonding_curve.virtual_token_reserves = virtual_token_reserves;
+        bonding_curve.reserved_tokens = reserved_tokens;
+
+        //  the price history starts at launch, before the creator dev-buy moves the price
+        let now = Clock::get()?.unix_timestamp;
+        let price_oracle = &mut self.price_oracle;
+        price_oracle.mint = self.token_mint.key();
+        price_oracle.bump = price_oracle_bump;
+        price_oracle.update_price_cumulative(PriceOracle::calc_spot_price(bonding_curve, None)?, now);
+        price_oracle.record(now);
 
         let signer_seeds: &[&[&[u8]]] = &[&[CONFIG_SEED_IN_BYTES, &[global_config_bump]]];
 
+        //  create the mint, token-2022 mints get their metadata here as well
+        self.create_mint(&name, &symbol, &uri, signer_seeds)?;
+        Self::create_associated_account(
+            &self.creator,
+            &self.curve_token_account,
+            &self.bonding_curve.to_account_info(),
+            &self.token_mint,
+            &self.token_program,
+            &self.associated_token_program,
+            &self.system_program,
+        )?;
+
         // minting token to bonding curve
-        token::mint_to(
+        token_interface::mint_to(
             CpiContext::new_with_signer(
                 self.token_program.to_account_info(),
-                token::MintTo {
+                token_interface::MintTo {
                     mint: self.token_mint.to_account_info(),
                     to: self.curve_token_account.to_account_info(),
                     authority: global_config.to_account_info(),
                 },
                 signer_seeds,
             ),
-            global_config.total_token_supply,
+            curve_tokens,
         )?;
 
-        //  create metadata
-        metadata::create_metadata_accounts_v3(
-            CpiContext::new_with_signer(
-                self.metadata_program.to_account_info(),
-                metadata::CreateMetadataAccountsV3 {
-                    metadata: self.token_metadata_account.to_account_info(),
-                    mint: self.token_mint.to_account_info(),
-                    mint_authority: global_config.to_account_info(),
-                    payer: self.admin.to_account_info(),
-                    update_authority: global_config.to_account_info(),
-                    system_program: self.system_program.to_account_info(),
-                    rent: self.rent.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            DataV2 {
-                name: name.clone(),
-                symbol: symbol.clone(),
-                uri: uri.clone(),
-                seller_fee_basis_points: 0,
-                creators: None,
-                collection: None,
-                uses: None,
-            },
-            false,
-            true,
-            None,
-        )?;
+        // minting the reserved allocation to the vesting escrow
+        match (vesting, &mut self.vesting, &self.vesting_token_account, vesting_bump) {
+            (Some(params), Some(vesting), Some(vesting_token_account), Some(vesting_bump)) => {
+                let start_ts = Clock::get()?.unix_timestamp;
+                vesting.set_inner(Vesting {
+                    mint: self.token_mint.key(),
+                    beneficiary: params.beneficiary,
+                    total_amount: reserved_tokens,
+                    claimed_amount: 0,
+                    start_ts,
+                    cliff_ts: start_ts
+                        .checked_add(params.cliff_seconds)
+                        .ok_or(CustomError::OverflowOrUnderflowOccurred)?,
+                    end_ts: start_ts
+                        .checked_add(params.duration_seconds)
+                        .ok_or(CustomError::OverflowOrUnderflowOccurred)?,
+                    bump: vesting_bump,
+                });
+
+                Self::create_associated_account(
+                    &self.creator,
+                    vesting_token_account,
+                    &vesting.to_account_info(),
+                    &self.token_mint,
+                    &self.token_program,
+                    &self.associated_token_program,
+                    &self.system_program,
+                )?;
+                token_interface::mint_to(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        token_interface::MintTo {
+                            mint: self.token_mint.to_account_info(),
+                            to: vesting_token_account.to_account_info(),
+                            authority: global_config.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    reserved_tokens,
+                )?;
+            }
+            (None, None, None, _) => {}
+            _ => return err!(CustomError::IncorrectVestingAccounts),
+        }
+
+        //  legacy SPL mints keep their metadata in a Metaplex account
+        if self.token_program.key() != spl_token_2022::ID {
+            match (&self.token_metadata_account, &self.metadata_program) {
+                (Some(token_metadata_account), Some(metadata_program)) => {
+                    metadata::create_metadata_accounts_v3(
+                        CpiContext::new_with_signer(
+                            metadata_program.to_account_info(),
+                            metadata::CreateMetadataAccountsV3 {
+                                metadata: token_metadata_account.to_account_info(),
+                                mint: self.token_mint.to_account_info(),
+                                mint_authority: global_config.to_account_info(),
+                                payer: self.creator.to_account_info(),
+                                update_authority: global_config.to_account_info(),
+                                system_program: self.system_program.to_account_info(),
+                                rent: self.rent.to_account_info(),
+                            },
+                            signer_seeds,
+                        ),
+                        DataV2 {
+                            name: name.clone(),
+                            symbol: symbol.clone(),
+                            uri: uri.clone(),
+                            seller_fee_basis_points: 0,
+                            creators: None,
+                            collection: None,
+                            uses: None,
+                        },
+                        false,
+                        true,
+                        None,
+                    )?;
+                }
+                _ => return err!(CustomError::IncorrectMetadataAccounts),
+            }
+        }
 
         //  revoke mint authority
-        token::set_authority(
+        token_interface::set_authority(
             CpiContext::new_with_signer(
                 self.token_program.to_account_info(),
-                token::SetAuthority {
+                token_interface::SetAuthority {
                     current_authority: global_config.to_account_info(),
                     account_or_mint: self.token_mint.to_account_info(),
                 },
@@ -140,13 +330,179 @@ impl<'info> Launch<'info> {
             None,
         )?;
 
-        emit!(LaunchEvent {
+        emit_cpi_event(&self.event_authority, event_authority_bump, &LaunchEvent {
+            creator: self.creator.key(),
             mint: self.token_mint.key(),
             name,
             symbol,
             uri,
-        });
+        })?;
+
+        //  creator dev-buy, nobody can trade between the launch and this buy
+        //  it counts towards the fee tier of the creator like any other buy
+        match (initial_buy_lamports, &self.creator_token_account, &mut self.trader_stats, trader_stats_bump) {
+            (Some(amount_in), Some(creator_token_account), Some(trader_stats), Some(trader_stats_bump)) => {
+                global_config.validate_fee_share_accounts(fee_share_accounts)?;
+
+                //  first trade of this wallet
+                if trader_stats.trader == Pubkey::default() {
+                    trader_stats.trader = self.creator.key();
+                    trader_stats.bump = trader_stats_bump;
+                }
+
+                Self::create_associated_account(
+                    &self.creator,
+                    creator_token_account,
+                    &self.creator.to_account_info(),
+                    &self.token_mint,
+                    &self.token_program,
+                    &self.associated_token_program,
+                    &self.system_program,
+                )?;
+
+                let bonding_curve = &mut self.bonding_curve;
+                let bonding_curve_pda = &mut bonding_curve.to_account_info();
+                let curve_completed = bonding_curve.buy(
+                    &self.token_mint.to_account_info(),
+                    &self.creator,
+                    bonding_curve_pda,
+                    &mut self.fee_recipient,
+                    fee_share_accounts,
+                    None,
+                    Some(trader_stats),
+                    &mut creator_token_account.to_account_info(),
+                    &mut self.curve_token_account.to_account_info(),
+                    SwapAmount::ExactIn { amount_in, min_amount_out },
+                    global_config,
+                    bonding_curve_bump,
+                    &self.system_program.to_account_info(),
+                    &self.token_program.to_account_info(),
+                    &self.event_authority,
+                    event_authority_bump,
+                )?;
+
+                if curve_completed {
+                    emit_cpi_event(&self.event_authority, event_authority_bump, &CurveCompletedEvent {
+                        mint: self.token_mint.key(),
+                        virtual_token_reserves: bonding_curve.virtual_token_reserves,
+                        virtual_lamport_reserves: bonding_curve.virtual_lamport_reserves,
+                        actual_lamport_reserves: bonding_curve.actual_lamport_reserves,
+                        slot: Clock::get()?.slot,
+                    })?;
+                }
+            }
+            (None, None, None, _) => {}
+            _ => return err!(CustomError::IncorrectInitialBuyAccounts),
+        }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    //  create the mint account and initialize it under the chosen token program
+    fn create_mint(&self, name: &str, symbol: &str, uri: &str, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let is_token_2022 = self.token_program.key() == spl_token_2022::ID;
+        let global_config = self.global_config.key();
+
+        //  token-2022 mints point at themselves for metadata, the metadata is appended to the account afterwards
+        let (space, lamports) = if is_token_2022 {
+            let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+                ExtensionType::MetadataPointer,
+            ])?;
+            let metadata = TokenMetadata {
+                update_authority: OptionalNonZeroPubkey::try_from(Some(global_config))?,
+                mint: self.token_mint.key(),
+                name: name.to_string(),
+                symbol: symbol.to_string(),
+                uri: uri.to_string(),
+                additional_metadata: vec![],
+            };
+            (space, self.rent.minimum_balance(space + metadata.tlv_size_of()?))
+        } else {
+            let space = token_interface::find_mint_account_size(None)?;
+            (space, self.rent.minimum_balance(space))
+        };
+
+        system_program::create_account(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: self.creator.to_account_info(),
+                    to: self.token_mint.to_account_info(),
+                },
+            ),
+            lamports,
+            space as u64,
+            &self.token_program.key(),
+        )?;
+
+        if is_token_2022 {
+            token_interface::metadata_pointer_initialize(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    token_interface::MetadataPointerInitialize {
+                        token_program_id: self.token_program.to_account_info(),
+                        mint: self.token_mint.to_account_info(),
+                    },
+                ),
+                Some(global_config),
+                Some(self.token_mint.key()),
+            )?;
+        }
+
+        token_interface::initialize_mint2(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token_interface::InitializeMint2 {
+                    mint: self.token_mint.to_account_info(),
+                },
+            ),
+            TOKEN_DECIMAL,
+            &global_config,
+            None,
+        )?;
+
+        if is_token_2022 {
+            token_interface::token_metadata_initialize(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    token_interface::TokenMetadataInitialize {
+                        token_program_id: self.token_program.to_account_info(),
+                        metadata: self.token_mint.to_account_info(),
+                        update_authority: self.global_config.to_account_info(),
+                        mint_authority: self.global_config.to_account_info(),
+                        mint: self.token_mint.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                name.to_string(),
+                symbol.to_string(),
+                uri.to_string(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    //  the associated token program checks the address, so the account can be passed unchecked
+    fn create_associated_account(
+        payer: &Signer<'info>,
+        token_account: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        token_mint: &Signer<'info>,
+        token_program: &Interface<'info, TokenInterface>,
+        associated_token_program: &Program<'info, AssociatedToken>,
+        system_program: &Program<'info, System>,
+    ) -> Result<()> {
+        associated_token::create(CpiContext::new(
+            associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: payer.to_account_info(),
+                associated_token: token_account.to_account_info(),
+                authority: authority.to_account_info(),
+                mint: token_mint.to_account_info(),
+                system_program: system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+        ))
+    }
+}
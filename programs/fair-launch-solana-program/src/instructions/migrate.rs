@@ -0,0 +1,157 @@
+use crate::{constants, errors::CustomError, events::CurveMigratedEvent, states::{BondingCurve, Config, Pool, PriceOracle}};
+use crate::utils::{emit_cpi_event, sol_transfer_from_pda, token_transfer_from_pda};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken},
+    token_interface::{self, Mint, TokenAccount, TokenInterface},
+};
+use constants::{CONFIG_SEED_IN_BYTES, LP_MINT_SEED_IN_BYTES, POOL_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES, TOKEN_DECIMAL};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+    #[account(
+        mut,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = bonding_curve,
+        associated_token::token_program = token_program
+    )]
+    curve_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [POOL_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump
+    )]
+    pool: Box<Account<'info, Pool>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [LP_MINT_SEED_IN_BYTES, pool.key().as_ref()],
+        bump,
+        mint::decimals = TOKEN_DECIMAL,
+        mint::authority = pool,
+        mint::token_program = token_program,
+    )]
+    lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = token_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+    #[account(address = associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+impl<'info> Migrate<'info> {
+    //  canonical graduation path, restricted to the authority like `withdraw_completed` so the two cannot race,
+    //  whichever runs first marks the curve and the other is rejected
+    pub fn handle(&mut self, bump_bonding_curve: u8, bump_pool: u8, bump_event_authority: u8) -> Result<()> {
+        let bonding_curve = &mut self.bonding_curve;
+
+        //  only completed curves graduate, and only once
+        require!(
+            bonding_curve.is_completed,
+            CustomError::CurveNotCompleted
+        );
+        require!(!bonding_curve.is_migrated, CustomError::CurveAlreadyMigrated);
+        require!(!bonding_curve.is_withdrawn, CustomError::CurveAlreadyWithdrawn);
+
+        //  the pool opens at the final curve price, the tokens that would price it lower are burned
+        let (lamport_reserves, token_reserves) = Pool::calc_seed_reserves(bonding_curve, self.curve_token_account.amount)?;
+        let tokens_burned = self.curve_token_account.amount - token_reserves;
+        if tokens_burned > 0 {
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    token_interface::Burn {
+                        mint: self.token_mint.to_account_info(),
+                        from: self.curve_token_account.to_account_info(),
+                        authority: bonding_curve.to_account_info(),
+                    },
+                    &[&BondingCurve::get_signer(&self.token_mint.key(), &bump_bonding_curve)],
+                ),
+                tokens_burned,
+            )?;
+        }
+
+        //  move the curve liquidity into the pool
+        token_transfer_from_pda(
+            &self.curve_token_account.to_account_info(),
+            &bonding_curve.to_account_info(),
+            &self.pool_token_account.to_account_info(),
+            &self.token_mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            &[&BondingCurve::get_signer(&self.token_mint.key(), &bump_bonding_curve)],
+            token_reserves,
+            self.token_mint.decimals,
+        )?;
+        sol_transfer_from_pda(
+            &mut bonding_curve.to_account_info(),
+            &mut self.pool.to_account_info(),
+            lamport_reserves,
+        )?;
+
+        //  the final curve price counts towards the TWAP until now, the pool prices the token afterwards
+        let now = Clock::get()?.unix_timestamp;
+        self.price_oracle.update_price_cumulative(PriceOracle::calc_spot_price(bonding_curve, None)?, now);
+        self.price_oracle.record(now);
+
+        bonding_curve.actual_lamport_reserves = 0;
+        bonding_curve.is_migrated = true;
+
+        let lp_supply = Pool::calc_initial_lp_supply(lamport_reserves, token_reserves)?;
+        self.pool.set_inner(Pool {
+            token_mint: self.token_mint.key(),
+            lp_mint: self.lp_mint.key(),
+            token_reserves,
+            lamport_reserves,
+            lp_supply,
+            bump: bump_pool,
+        });
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &CurveMigratedEvent {
+            mint: self.token_mint.key(),
+            pool: self.pool.key(),
+            lamport_reserves,
+            token_reserves,
+            tokens_burned,
+            lp_supply,
+        })?;
+
+        Ok(())
+    }
+}
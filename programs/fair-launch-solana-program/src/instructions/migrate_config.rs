@@ -0,0 +1,38 @@
+use crate::{errors::CustomError, states::Config, utils::resize_account};
+use anchor_lang::prelude::*;
+use crate::constants::{ADMIN_ADDRESS, CONFIG_SEED_IN_BYTES};
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    //  the deploy key only matters for legacy-length configs, `Config::migrate_data` rejects
+    //  configs already on the current layout, whose authority is managed through the config itself
+    #[account(mut, address = ADMIN_ADDRESS @CustomError::NotAuthorized)]
+    admin: Signer<'info>,
+
+    /// CHECK: deserialized manually, the account still has the legacy layout
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        owner = crate::ID,
+    )]
+    global_config: UncheckedAccount<'info>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    pub fn handle(&mut self) -> Result<()> {
+        let mut global_config = self.global_config.to_account_info();
+        let new_data = Config::migrate_data(&global_config.try_borrow_data()?)?;
+
+        resize_account(
+            &mut global_config,
+            &self.admin,
+            &self.system_program.to_account_info(),
+            new_data.len(),
+        )?;
+        global_config.try_borrow_mut_data()?.copy_from_slice(&new_data);
+
+        Ok(())
+    }
+}
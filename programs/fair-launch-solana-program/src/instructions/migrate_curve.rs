@@ -0,0 +1,56 @@
+use crate::{states::{BondingCurve, PriceOracle}, utils::resize_account};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::constants::PRICE_ORACLE_SEED_IN_BYTES;
+
+#[derive(Accounts)]
+pub struct MigrateCurve<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: deserialized manually, the account still has the legacy layout
+    #[account(
+        mut,
+        seeds = [&token_mint.key().to_bytes()],
+        bump,
+        owner = crate::ID,
+    )]
+    bonding_curve: UncheckedAccount<'info>,
+    //  curves launched before the price oracle existed get theirs here
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PriceOracle::INIT_SPACE,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateCurve<'info> {
+    //  permissionless, the result only depends on the stored curve and the payer covers the extra rent
+    pub fn handle(&mut self, bump_price_oracle: u8) -> Result<()> {
+        let mut bonding_curve = self.bonding_curve.to_account_info();
+
+        //  curves already on the current layout only get their price oracle
+        if bonding_curve.data_len() != 8 + BondingCurve::INIT_SPACE {
+            let new_data = BondingCurve::migrate_data(&bonding_curve.try_borrow_data()?)?;
+            resize_account(
+                &mut bonding_curve,
+                &self.payer,
+                &self.system_program.to_account_info(),
+                new_data.len(),
+            )?;
+            bonding_curve.try_borrow_mut_data()?.copy_from_slice(&new_data);
+        }
+
+        //  the price history starts with the first trade after the migration
+        let price_oracle = &mut self.price_oracle;
+        price_oracle.mint = self.token_mint.key();
+        price_oracle.bump = bump_price_oracle;
+
+        Ok(())
+    }
+}
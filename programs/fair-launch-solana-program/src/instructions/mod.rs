@@ -1,7 +1,58 @@
-pub mod configure;
-pub use configure::*;
+pub mod initialize_config;
+pub use initialize_config::*;
+pub mod update_fees;
+pub use update_fees::*;
+pub mod update_fee_recipient;
+pub use update_fee_recipient::*;
+pub mod update_fee_shares;
+pub use update_fee_shares::*;
+pub mod update_fee_tiers;
+pub use update_fee_tiers::*;
+pub mod update_curve_params;
+pub use update_curve_params::*;
+pub mod update_launch_access;
+pub use update_launch_access::*;
+pub mod migrate_config;
+pub use migrate_config::*;
+pub mod migrate_curve;
+pub use migrate_curve::*;
+pub mod propose_authority;
+pub use propose_authority::*;
+pub mod accept_authority;
+pub use accept_authority::*;
+pub mod set_paused;
+pub use set_paused::*;
+pub mod set_curve_paused;
+pub use set_curve_paused::*;
 
 pub mod launch;
 pub use launch::*;
 pub mod swap;
 pub use swap::*;
+pub mod quote;
+pub use quote::*;
+pub mod twap;
+pub use twap::*;
+
+pub mod migrate;
+pub use migrate::*;
+pub mod pool_swap;
+pub use pool_swap::*;
+pub mod pool_deposit;
+pub use pool_deposit::*;
+pub mod pool_withdraw;
+pub use pool_withdraw::*;
+pub mod withdraw_completed;
+pub use withdraw_completed::*;
+pub mod claim_refund;
+pub use claim_refund::*;
+pub mod claim_vested;
+pub use claim_vested::*;
+pub mod burn_reserved_tokens;
+pub use burn_reserved_tokens::*;
+pub mod claim_creator_fees;
+pub use claim_creator_fees::*;
+pub mod register_referrer;
+pub use register_referrer::*;
+pub mod claim_referral_fees;
+pub use claim_referral_fees::*;
@@ -0,0 +1,150 @@
+use crate::{constants, errors::CustomError, events::PoolLiquidityEvent, states::{Config, Pool, PriceOracle}};
+use crate::utils::{emit_cpi_event, sol_transfer_from_user, token_transfer_from_user};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken},
+    token_interface::{self, Mint, TokenAccount, TokenInterface},
+};
+use constants::{CONFIG_SEED_IN_BYTES, LP_MINT_SEED_IN_BYTES, POOL_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PoolDeposit<'info> {
+    #[account(mut)]
+    user: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+    )]
+    global_config: Box<Account<'info, Config>>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [POOL_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = pool.bump
+    )]
+    pool: Box<Account<'info, Pool>>,
+    #[account(
+        mut,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    #[account(
+        mut,
+        seeds = [LP_MINT_SEED_IN_BYTES, pool.key().as_ref()],
+        bump,
+    )]
+    lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = lp_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    user_lp_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+    #[account(address = associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+impl<'info> PoolDeposit<'info> {
+    pub fn handle(&mut self, lp_amount: u64, max_lamports_in: u64, max_tokens_in: u64, bump_event_authority: u8) -> Result<()> {
+        require!(
+            !self.global_config.paused,
+            CustomError::ProgramPaused
+        );
+
+        require!(lp_amount > 0, CustomError::IncorrectValue);
+
+        let pool = &mut self.pool;
+        //  LP amounts are priced against the LP supply, which must not be zero
+        require!(pool.lp_supply > 0, CustomError::PoolEmpty);
+
+        let (lamports, tokens) = pool.calc_deposit(lp_amount)?;
+
+        //  liquidity changes move the price only by rounding, the price held so far is accumulated all the same
+        let now = Clock::get()?.unix_timestamp;
+        self.price_oracle.update_price_cumulative(pool.calc_spot_price()?, now);
+        self.price_oracle.record(now);
+
+        //  slippage protection
+        require!(
+            lamports <= max_lamports_in && tokens <= max_tokens_in,
+            CustomError::AmountInTooLarge
+        );
+
+        sol_transfer_from_user(
+            &self.user,
+            &pool.to_account_info(),
+            &self.system_program.to_account_info(),
+            lamports,
+        )?;
+        token_transfer_from_user(
+            &self.user_token_account.to_account_info(),
+            &self.user.to_account_info(),
+            &self.pool_token_account.to_account_info(),
+            &self.token_mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            tokens,
+            self.token_mint.decimals,
+        )?;
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token_interface::MintTo {
+                    mint: self.lp_mint.to_account_info(),
+                    to: self.user_lp_token_account.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&Pool::get_signer(&self.token_mint.key(), &pool.bump)],
+            ),
+            lp_amount,
+        )?;
+
+        let new_lamport_reserves = pool
+            .lamport_reserves
+            .checked_add(lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let new_token_reserves = pool
+            .token_reserves
+            .checked_add(tokens)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let new_lp_supply = pool
+            .lp_supply
+            .checked_add(lp_amount)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        pool.update_reserves(new_lamport_reserves, new_token_reserves, new_lp_supply);
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &PoolLiquidityEvent {
+            provider: self.user.key(),
+            asset: self.token_mint.key(),
+            is_deposit: true,
+            lamports,
+            tokens,
+            lp_amount,
+        })?;
+
+        Ok(())
+    }
+}
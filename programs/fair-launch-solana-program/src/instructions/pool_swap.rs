@@ -0,0 +1,145 @@
+use crate::{constants, errors::CustomError, events::PoolTradeEvent, states::{Config, Pool, PriceOracle}};
+use crate::utils::{emit_cpi_event, sol_transfer_from_pda, sol_transfer_from_user, token_transfer_from_pda, token_transfer_from_user};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use constants::{CONFIG_SEED_IN_BYTES, POOL_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PoolSwap<'info> {
+    #[account(mut)]
+    user: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+    )]
+    global_config: Box<Account<'info, Config>>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [POOL_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = pool.bump
+    )]
+    pool: Box<Account<'info, Pool>>,
+    #[account(
+        mut,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+    #[account(address = associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+impl<'info> PoolSwap<'info> {
+    pub fn handle(&mut self, amount_in: u64, is_buy: bool, min_amount_out: u64, bump_event_authority: u8) -> Result<()> {
+        require!(
+            !self.global_config.paused,
+            CustomError::ProgramPaused
+        );
+
+        let pool = &mut self.pool;
+        //  the reserves are only empty once every LP token, the locked seed supply included, is gone
+        require!(pool.lp_supply > 0, CustomError::PoolEmpty);
+
+        let amount_out = pool.calc_amount_out(amount_in, is_buy)?;
+
+        //  the price held since the previous pool update is accumulated before this swap moves it
+        let now = Clock::get()?.unix_timestamp;
+        self.price_oracle.update_price_cumulative(pool.calc_spot_price()?, now);
+        self.price_oracle.record(now);
+
+        //  slippage protection
+        require!(
+            amount_out >= min_amount_out,
+            CustomError::ReturnAmountTooSmall
+        );
+
+        let (new_lamport_reserves, new_token_reserves) = if is_buy {
+            //  buy - swap sol for token
+            sol_transfer_from_user(
+                &self.user,
+                &pool.to_account_info(),
+                &self.system_program.to_account_info(),
+                amount_in,
+            )?;
+            token_transfer_from_pda(
+                &self.pool_token_account.to_account_info(),
+                &pool.to_account_info(),
+                &self.user_token_account.to_account_info(),
+                &self.token_mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                &[&Pool::get_signer(&self.token_mint.key(), &pool.bump)],
+                amount_out,
+                self.token_mint.decimals,
+            )?;
+
+            (
+                pool.lamport_reserves.checked_add(amount_in),
+                pool.token_reserves.checked_sub(amount_out),
+            )
+        } else {
+            //  sell - swap token for sol
+            token_transfer_from_user(
+                &self.user_token_account.to_account_info(),
+                &self.user.to_account_info(),
+                &self.pool_token_account.to_account_info(),
+                &self.token_mint.to_account_info(),
+                &self.token_program.to_account_info(),
+                amount_in,
+                self.token_mint.decimals,
+            )?;
+            sol_transfer_from_pda(
+                &mut pool.to_account_info(),
+                &mut self.user.to_account_info(),
+                amount_out,
+            )?;
+
+            (
+                pool.lamport_reserves.checked_sub(amount_out),
+                pool.token_reserves.checked_add(amount_in),
+            )
+        };
+
+        let lp_supply = pool.lp_supply;
+        pool.update_reserves(
+            new_lamport_reserves.ok_or(CustomError::OverflowOrUnderflowOccurred)?,
+            new_token_reserves.ok_or(CustomError::OverflowOrUnderflowOccurred)?,
+            lp_supply,
+        );
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &PoolTradeEvent {
+            trader: self.user.key(),
+            asset: self.token_mint.key(),
+            is_buy,
+            amount_in,
+            amount_out,
+        })?;
+
+        Ok(())
+    }
+}
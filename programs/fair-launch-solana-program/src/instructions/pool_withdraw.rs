@@ -0,0 +1,139 @@
+use crate::{constants, errors::CustomError, events::PoolLiquidityEvent, states::{Pool, PriceOracle}};
+use crate::utils::{emit_cpi_event, sol_transfer_from_pda, token_transfer_from_pda};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken},
+    token_interface::{self, Mint, TokenAccount, TokenInterface},
+};
+use constants::{LP_MINT_SEED_IN_BYTES, POOL_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PoolWithdraw<'info> {
+    #[account(mut)]
+    user: Signer<'info>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [POOL_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = pool.bump
+    )]
+    pool: Box<Account<'info, Pool>>,
+    #[account(
+        mut,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    #[account(
+        mut,
+        seeds = [LP_MINT_SEED_IN_BYTES, pool.key().as_ref()],
+        bump,
+    )]
+    lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    user_lp_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+    #[account(address = associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+impl<'info> PoolWithdraw<'info> {
+    pub fn handle(&mut self, lp_amount: u64, min_lamports_out: u64, min_tokens_out: u64, bump_event_authority: u8) -> Result<()> {
+        //  the global pause halts trading only, liquidity providers can always exit
+        require!(lp_amount > 0, CustomError::IncorrectValue);
+
+        let pool = &mut self.pool;
+        //  LP amounts are priced against the LP supply, which must not be zero
+        require!(pool.lp_supply > 0, CustomError::PoolEmpty);
+
+        let (lamports, tokens) = pool.calc_withdraw(lp_amount)?;
+
+        //  liquidity changes move the price only by rounding, the price held so far is accumulated all the same
+        let now = Clock::get()?.unix_timestamp;
+        self.price_oracle.update_price_cumulative(pool.calc_spot_price()?, now);
+        self.price_oracle.record(now);
+
+        //  slippage protection
+        require!(
+            lamports >= min_lamports_out && tokens >= min_tokens_out,
+            CustomError::ReturnAmountTooSmall
+        );
+
+        token_interface::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token_interface::Burn {
+                    mint: self.lp_mint.to_account_info(),
+                    from: self.user_lp_token_account.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            lp_amount,
+        )?;
+        token_transfer_from_pda(
+            &self.pool_token_account.to_account_info(),
+            &pool.to_account_info(),
+            &self.user_token_account.to_account_info(),
+            &self.token_mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            &[&Pool::get_signer(&self.token_mint.key(), &pool.bump)],
+            tokens,
+            self.token_mint.decimals,
+        )?;
+        sol_transfer_from_pda(
+            &mut pool.to_account_info(),
+            &mut self.user.to_account_info(),
+            lamports,
+        )?;
+
+        let new_lamport_reserves = pool
+            .lamport_reserves
+            .checked_sub(lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let new_token_reserves = pool
+            .token_reserves
+            .checked_sub(tokens)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let new_lp_supply = pool
+            .lp_supply
+            .checked_sub(lp_amount)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        pool.update_reserves(new_lamport_reserves, new_token_reserves, new_lp_supply);
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &PoolLiquidityEvent {
+            provider: self.user.key(),
+            asset: self.token_mint.key(),
+            is_deposit: false,
+            lamports,
+            tokens,
+            lp_amount,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{errors::CustomError, events::AuthorityProposedEvent, states::Config};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> ProposeAuthority<'info> {
+    //  the transfer only takes effect once the new authority accepts it, proposing the default pubkey cancels it
+    pub fn handle(&mut self, new_authority: Pubkey, bump_event_authority: u8) -> Result<()> {
+        self.global_config.pending_authority = new_authority;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &AuthorityProposedEvent {
+            authority: self.authority.key(),
+            pending_authority: new_authority,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{constants, errors::CustomError, states::{BondingCurve, Config, SwapAmount, SwapQuote, TraderStats}};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use constants::{CONFIG_SEED_IN_BYTES, TRADER_STATS_SEED_IN_BYTES};
+
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    #[account(
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+    )]
+    global_config: Box<Account<'info, Config>>,
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+    //  volume of the trader, without it the quote is priced at the base fee like the first trade of a wallet
+    #[account(
+        seeds = [TRADER_STATS_SEED_IN_BYTES, trader_stats.trader.as_ref()],
+        bump = trader_stats.bump
+    )]
+    trader_stats: Option<Box<Account<'info, TraderStats>>>,
+}
+
+impl<'info> Quote<'info> {
+    pub fn handle(&self, amount_in: u64, is_buy: bool) -> Result<SwapQuote> {
+        //  completed or expired curves cannot be traded
+        require!(
+            !self.bonding_curve.is_completed,
+            CustomError::CurveAlreadyCompleted
+        );
+        require!(
+            !self.bonding_curve.is_expired(Clock::get()?.unix_timestamp),
+            CustomError::CurveExpired
+        );
+
+        self.bonding_curve.quote(
+            SwapAmount::ExactIn { amount_in, min_amount_out: 0 },
+            is_buy,
+            &self.global_config,
+            self.trader_stats.as_deref().map(|trader_stats| &**trader_stats),
+        )
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{constants, states::Referrer};
+use anchor_lang::{prelude::*, system_program};
+use constants::REFERRER_SEED_IN_BYTES;
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    wallet: Signer<'info>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + Referrer::INIT_SPACE,
+        seeds = [REFERRER_SEED_IN_BYTES, wallet.key().as_ref()],
+        bump
+    )]
+    referrer: Box<Account<'info, Referrer>>,
+
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+impl<'info> RegisterReferrer<'info> {
+    pub fn handle(&mut self, bump: u8) -> Result<()> {
+        self.referrer.set_inner(Referrer {
+            wallet: self.wallet.key(),
+            accrued_lamports: 0,
+            total_earned_lamports: 0,
+            bump,
+        });
+
+        Ok(())
+    }
+}
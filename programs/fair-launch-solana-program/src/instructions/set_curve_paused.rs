@@ -0,0 +1,40 @@
+use crate::{errors::CustomError, events::CurvePausedEvent, states::{BondingCurve, Config}};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCurvePaused<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+}
+
+impl<'info> SetCurvePaused<'info> {
+    pub fn handle(&mut self, paused: bool, bump_event_authority: u8) -> Result<()> {
+        self.bonding_curve.trading_paused = paused;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &CurvePausedEvent {
+            mint: self.token_mint.key(),
+            paused,
+            slot: Clock::get()?.slot,
+        })?;
+
+        Ok(())
+    }
+}
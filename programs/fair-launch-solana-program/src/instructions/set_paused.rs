@@ -0,0 +1,31 @@
+use crate::{errors::CustomError, events::ProgramPausedEvent, states::Config};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> SetPaused<'info> {
+    pub fn handle(&mut self, paused: bool, bump_event_authority: u8) -> Result<()> {
+        self.global_config.paused = paused;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ProgramPausedEvent {
+            paused,
+            slot: Clock::get()?.slot,
+        })?;
+
+        Ok(())
+    }
+}
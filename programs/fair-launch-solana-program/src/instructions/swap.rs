@@ -1,11 +1,13 @@
-use crate::{constants, errors::CustomError, states::{BondingCurve, Config}};
+use crate::{constants, errors::CustomError, events::CurveCompletedEvent, states::{BondingCurve, Config, PriceOracle, Referrer, SwapAmount, TraderStats}};
+use crate::utils::emit_cpi_event;
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     associated_token::{self, AssociatedToken},
-    token::{self, Mint, Token, TokenAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
-use constants::CONFIG_SEED_IN_BYTES;
+use constants::{CONFIG_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES, TRADER_STATS_SEED_IN_BYTES};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Swap<'info> {
     #[account(mut)]
@@ -28,23 +30,44 @@ pub struct Swap<'info> {
     )]
     bonding_curve: Box<Account<'info, BondingCurve>>,
 
-    token_mint: Box<Account<'info, Mint>>,
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         mut,
         associated_token::mint = token_mint,
-        associated_token::authority = bonding_curve
+        associated_token::authority = bonding_curve,
+        associated_token::token_program = token_program
     )]
-    curve_token_account: Box<Account<'info, TokenAccount>>,
+    curve_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = token_mint,
-        associated_token::authority = user
+        associated_token::authority = user,
+        associated_token::token_program = token_program
     )]
-    user_token_account: Box<Account<'info, TokenAccount>>,
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    //  only needed when the trade was referred
+    #[account(
+        mut,
+        constraint = referrer.wallet != user.key() @CustomError::IncorrectReferrer
+    )]
+    referrer: Option<Box<Account<'info, Referrer>>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderStats::INIT_SPACE,
+        seeds = [TRADER_STATS_SEED_IN_BYTES, user.key().as_ref()],
+        bump
+    )]
+    trader_stats: Box<Account<'info, TraderStats>>,
+    #[account(
+        mut,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
 
-    #[account(address = token::ID)]
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     #[account(address = associated_token::ID)]
     associated_token_program: Program<'info, AssociatedToken>,
     #[account(address = system_program::ID)]
@@ -52,50 +75,145 @@ pub struct Swap<'info> {
 }
 
 impl<'info> Swap<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn handle(
         &mut self,
         amount_in: u64,
         is_buy: bool,
+        min_amount_out: u64,
         bump_bonding_curve: u8,
+        bump_trader_stats: u8,
+        bump_event_authority: u8,
+        fee_share_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.execute(
+            SwapAmount::ExactIn { amount_in, min_amount_out },
+            is_buy,
+            bump_bonding_curve,
+            bump_trader_stats,
+            bump_event_authority,
+            fee_share_accounts,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_exact_out(
+        &mut self,
+        amount_out: u64,
+        is_buy: bool,
+        max_amount_in: u64,
+        bump_bonding_curve: u8,
+        bump_trader_stats: u8,
+        bump_event_authority: u8,
+        fee_share_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.execute(
+            SwapAmount::ExactOut { amount_out, max_amount_in },
+            is_buy,
+            bump_bonding_curve,
+            bump_trader_stats,
+            bump_event_authority,
+            fee_share_accounts,
+        )
+    }
+
+    fn execute(
+        &mut self,
+        amount: SwapAmount,
+        is_buy: bool,
+        bump_bonding_curve: u8,
+        bump_trader_stats: u8,
+        bump_event_authority: u8,
+        fee_share_accounts: &[AccountInfo<'info>],
     ) -> Result<()> {
         let bonding_curve = &mut self.bonding_curve;
 
+        //  check trading is not halted, globally or on this curve
+        require!(
+            !self.global_config.paused,
+            CustomError::ProgramPaused
+        );
+        require!(
+            !bonding_curve.trading_paused,
+            CustomError::TradingPaused
+        );
+
         //  check curve is not completed
         require!(
-            bonding_curve.is_completed == false,
+            !bonding_curve.is_completed,
             CustomError::CurveAlreadyCompleted
         );
+        //  expired curves only allow refunds
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !bonding_curve.is_expired(now),
+            CustomError::CurveExpired
+        );
+
+        //  the price held since the previous trade is accumulated before this trade moves it
+        self.price_oracle.update_price_cumulative(PriceOracle::calc_spot_price(bonding_curve, None)?, now);
+        self.price_oracle.record(now);
 
         let bonding_curve_pda = &mut bonding_curve.to_account_info();
-        let global_config: &Box<Account<'info, Config>> = &self.global_config;
+        let global_config: &Account<'info, Config> = &self.global_config;
+        global_config.validate_fee_share_accounts(fee_share_accounts)?;
+
+        //  first trade of this wallet
+        let trader_stats = &mut self.trader_stats;
+        if trader_stats.trader == Pubkey::default() {
+            trader_stats.trader = self.user.key();
+            trader_stats.bump = bump_trader_stats;
+        }
+        if let Some(referrer) = &self.referrer {
+            trader_stats.bind_referrer(referrer.wallet)?;
+        }
 
         if is_buy {
             //  buy - swap sol for token
-            let _curve_completed = bonding_curve.buy(
-                &self.token_mint,
-                global_config.lamports_needed_to_complete_curve,
+            let curve_completed = bonding_curve.buy(
+                &self.token_mint.to_account_info(),
                 &self.user,
                 bonding_curve_pda,
                 &mut self.fee_recipient,
+                fee_share_accounts,
+                self.referrer.as_deref_mut(),
+                Some(trader_stats),
                 &mut self.user_token_account.to_account_info(),
                 &mut self.curve_token_account.to_account_info(),
-                amount_in,
-                global_config.buy_fee_percent,
+                amount,
+                global_config,
                 bump_bonding_curve,
                 &self.system_program.to_account_info(),
-                &self.token_program.to_account_info()
+                &self.token_program.to_account_info(),
+                &self.event_authority,
+                bump_event_authority,
             )?;
+
+            if curve_completed {
+                emit_cpi_event(&self.event_authority, bump_event_authority, &CurveCompletedEvent {
+                    mint: self.token_mint.key(),
+                    virtual_token_reserves: bonding_curve.virtual_token_reserves,
+                    virtual_lamport_reserves: bonding_curve.virtual_lamport_reserves,
+                    actual_lamport_reserves: bonding_curve.actual_lamport_reserves,
+                    slot: Clock::get()?.slot,
+                })?;
+            }
         } else {
             bonding_curve.sell(
-                &self.token_mint,
+                &self.token_mint.to_account_info(),
                 &mut self.user.to_account_info(),
                 bonding_curve_pda,
                 &mut self.fee_recipient,
+                fee_share_accounts,
+                self.referrer.as_deref_mut(),
+                Some(trader_stats),
                 &mut self.user_token_account.to_account_info(),
                 &mut self.curve_token_account.to_account_info(),
-                amount_in,
-                global_config.sell_fee_percent,
-                &self.token_program.to_account_info()
+                amount,
+                global_config,
+                &self.token_program.to_account_info(),
+                &self.event_authority,
+                bump_event_authority,
             )?;
         }
 
@@ -0,0 +1,39 @@
+use crate::{constants, states::{BondingCurve, Pool, PriceOracle}};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use constants::{POOL_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES};
+
+#[derive(Accounts)]
+pub struct Twap<'info> {
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+    #[account(
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    //  only needed once the curve migrated, the pool prices the token from then on
+    #[account(
+        seeds = [POOL_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = pool.bump
+    )]
+    pool: Option<Box<Account<'info, Pool>>>,
+}
+
+impl<'info> Twap<'info> {
+    //  spot price averaged over at least the last `window_seconds`, scaled by `PRICE_PRECISION`,
+    //  the history continues across the migration into the pool, withdrawn curves have no price
+    pub fn handle(&self, window_seconds: i64) -> Result<u64> {
+        let spot_price = PriceOracle::calc_spot_price(&self.bonding_curve, self.pool.as_deref().map(|pool| &**pool))?;
+
+        self.price_oracle.calc_twap(
+            spot_price,
+            Clock::get()?.unix_timestamp,
+            window_seconds,
+        )
+    }
+}
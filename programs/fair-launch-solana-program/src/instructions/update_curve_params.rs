@@ -0,0 +1,40 @@
+use crate::{errors::CustomError, events::{ConfigUpdate, ConfigUpdatedEvent}, states::{Config, CurveParams}};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateCurveParams<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> UpdateCurveParams<'info> {
+    //  existing curves keep the reserves they were launched with, the completion threshold applies to every curve
+    pub fn handle(&mut self, curve_params: CurveParams, bump_event_authority: u8) -> Result<()> {
+        let global_config = &mut self.global_config;
+        let update = ConfigUpdate::CurveParams {
+            old_curve_params: global_config.curve_params(),
+            new_curve_params: curve_params.clone(),
+        };
+
+        global_config.set_curve_params(curve_params);
+
+        global_config.validate()?;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ConfigUpdatedEvent {
+            authority: self.authority.key(),
+            update,
+        })?;
+
+        Ok(())
+    }
+}
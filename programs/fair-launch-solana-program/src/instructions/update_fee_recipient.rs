@@ -0,0 +1,42 @@
+use crate::{errors::CustomError, events::{ConfigUpdate, ConfigUpdatedEvent}, states::Config};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFeeRecipient<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> UpdateFeeRecipient<'info> {
+    pub fn handle(&mut self, fee_recipient: Pubkey, withdraw_destination: Pubkey, bump_event_authority: u8) -> Result<()> {
+        let global_config = &mut self.global_config;
+        let update = ConfigUpdate::FeeRecipient {
+            old_fee_recipient: global_config.fee_recipient,
+            new_fee_recipient: fee_recipient,
+            old_withdraw_destination: global_config.withdraw_destination,
+            new_withdraw_destination: withdraw_destination,
+        };
+
+        global_config.fee_recipient = fee_recipient;
+        global_config.withdraw_destination = withdraw_destination;
+
+        global_config.validate()?;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ConfigUpdatedEvent {
+            authority: self.authority.key(),
+            update,
+        })?;
+
+        Ok(())
+    }
+}
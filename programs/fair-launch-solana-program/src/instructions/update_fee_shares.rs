@@ -0,0 +1,41 @@
+use crate::{errors::CustomError, events::{ConfigUpdate, ConfigUpdatedEvent}, states::{Config, FeeShare}};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFeeShares<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> UpdateFeeShares<'info> {
+    pub fn handle(&mut self, fee_shares: Vec<FeeShare>, bump_event_authority: u8, fee_share_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let global_config = &mut self.global_config;
+        let old_fee_shares = global_config.active_fee_shares().to_vec();
+
+        global_config.set_fee_shares(fee_shares)?;
+        let update = ConfigUpdate::FeeShares {
+            old_fee_shares,
+            new_fee_shares: global_config.active_fee_shares().to_vec(),
+        };
+
+        global_config.validate()?;
+        global_config.validate_fee_share_recipients(fee_share_accounts, &Rent::get()?)?;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ConfigUpdatedEvent {
+            authority: self.authority.key(),
+            update,
+        })?;
+
+        Ok(())
+    }
+}
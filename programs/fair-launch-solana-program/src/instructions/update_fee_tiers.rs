@@ -0,0 +1,41 @@
+use crate::{errors::CustomError, events::{ConfigUpdate, ConfigUpdatedEvent}, states::{Config, FeeTier}};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFeeTiers<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> UpdateFeeTiers<'info> {
+    //  tiers apply to the next trade of every wallet, volume already traded counts towards them
+    pub fn handle(&mut self, fee_tiers: Vec<FeeTier>, bump_event_authority: u8) -> Result<()> {
+        let global_config = &mut self.global_config;
+        let old_fee_tiers = global_config.active_fee_tiers().to_vec();
+
+        global_config.set_fee_tiers(fee_tiers)?;
+        let update = ConfigUpdate::FeeTiers {
+            old_fee_tiers,
+            new_fee_tiers: global_config.active_fee_tiers().to_vec(),
+        };
+
+        global_config.validate()?;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ConfigUpdatedEvent {
+            authority: self.authority.key(),
+            update,
+        })?;
+
+        Ok(())
+    }
+}
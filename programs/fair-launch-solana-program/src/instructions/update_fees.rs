@@ -0,0 +1,59 @@
+use crate::{errors::CustomError, events::{ConfigUpdate, ConfigUpdatedEvent}, states::Config};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFees<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> UpdateFees<'info> {
+    pub fn handle(
+        &mut self,
+        buy_fee_bps: u16,
+        sell_fee_bps: u16,
+        creator_fee_bps: u16,
+        referral_fee_bps: u16,
+        launch_fee_lamports: u64,
+        bump_event_authority: u8,
+    ) -> Result<()> {
+        let global_config = &mut self.global_config;
+        let update = ConfigUpdate::Fees {
+            old_buy_fee_bps: global_config.buy_fee_bps,
+            new_buy_fee_bps: buy_fee_bps,
+            old_sell_fee_bps: global_config.sell_fee_bps,
+            new_sell_fee_bps: sell_fee_bps,
+            old_creator_fee_bps: global_config.creator_fee_bps,
+            new_creator_fee_bps: creator_fee_bps,
+            old_referral_fee_bps: global_config.referral_fee_bps,
+            new_referral_fee_bps: referral_fee_bps,
+            old_launch_fee_lamports: global_config.launch_fee_lamports,
+            new_launch_fee_lamports: launch_fee_lamports,
+        };
+
+        global_config.buy_fee_bps = buy_fee_bps;
+        global_config.sell_fee_bps = sell_fee_bps;
+        global_config.creator_fee_bps = creator_fee_bps;
+        global_config.referral_fee_bps = referral_fee_bps;
+        global_config.launch_fee_lamports = launch_fee_lamports;
+
+        global_config.validate()?;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ConfigUpdatedEvent {
+            authority: self.authority.key(),
+            update,
+        })?;
+
+        Ok(())
+    }
+}
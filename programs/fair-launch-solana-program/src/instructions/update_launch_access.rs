@@ -0,0 +1,39 @@
+use crate::{errors::CustomError, events::{ConfigUpdate, ConfigUpdatedEvent}, states::Config};
+use crate::utils::emit_cpi_event;
+use anchor_lang::prelude::*;
+use crate::constants::CONFIG_SEED_IN_BYTES;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateLaunchAccess<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+}
+
+impl<'info> UpdateLaunchAccess<'info> {
+    pub fn handle(&mut self, admin_only_launch: bool, bump_event_authority: u8) -> Result<()> {
+        let global_config = &mut self.global_config;
+        let update = ConfigUpdate::LaunchAccess {
+            old_admin_only_launch: global_config.admin_only_launch,
+            new_admin_only_launch: admin_only_launch,
+        };
+
+        global_config.admin_only_launch = admin_only_launch;
+
+        global_config.validate()?;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &ConfigUpdatedEvent {
+            authority: self.authority.key(),
+            update,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{constants, errors::CustomError, events::CurveWithdrawnEvent, states::{BondingCurve, Config, PriceOracle}};
+use crate::utils::{emit_cpi_event, sol_transfer_from_pda, token_transfer_from_pda};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use constants::{CONFIG_SEED_IN_BYTES, PRICE_ORACLE_SEED_IN_BYTES};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawCompleted<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED_IN_BYTES],
+        bump,
+        has_one = authority @CustomError::NotAuthorized,
+    )]
+    global_config: Box<Account<'info, Config>>,
+    /// CHECK: should be same with the address in the global_config
+    #[account(
+        mut,
+        constraint = global_config.withdraw_destination == withdraw_destination.key() @CustomError::IncorrectWithdrawDestination
+    )]
+    withdraw_destination: AccountInfo<'info>,
+
+    token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [&token_mint.key().to_bytes()],
+        bump
+    )]
+    bonding_curve: Box<Account<'info, BondingCurve>>,
+    #[account(
+        mut,
+        seeds = [PRICE_ORACLE_SEED_IN_BYTES, token_mint.key().as_ref()],
+        bump = price_oracle.bump
+    )]
+    price_oracle: Box<Account<'info, PriceOracle>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = bonding_curve,
+        associated_token::token_program = token_program
+    )]
+    curve_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint,
+        associated_token::authority = withdraw_destination,
+        associated_token::token_program = token_program
+    )]
+    destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    token_program: Interface<'info, TokenInterface>,
+    #[account(address = associated_token::ID)]
+    associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawCompleted<'info> {
+    //  off-program alternative to `migrate`, the authority picks one of the two for every completed curve
+    pub fn handle(&mut self, bump_bonding_curve: u8, bump_event_authority: u8) -> Result<()> {
+        let bonding_curve = &mut self.bonding_curve;
+
+        //  only completed curves can be withdrawn, and only once
+        require!(
+            bonding_curve.is_completed,
+            CustomError::CurveNotCompleted
+        );
+        require!(!bonding_curve.is_migrated, CustomError::CurveAlreadyMigrated);
+        require!(!bonding_curve.is_withdrawn, CustomError::CurveAlreadyWithdrawn);
+
+        let lamports = bonding_curve.actual_lamport_reserves;
+        let tokens = self.curve_token_account.amount;
+
+        token_transfer_from_pda(
+            &self.curve_token_account.to_account_info(),
+            &bonding_curve.to_account_info(),
+            &self.destination_token_account.to_account_info(),
+            &self.token_mint.to_account_info(),
+            &self.token_program.to_account_info(),
+            &[&BondingCurve::get_signer(&self.token_mint.key(), &bump_bonding_curve)],
+            tokens,
+            self.token_mint.decimals,
+        )?;
+        sol_transfer_from_pda(
+            &mut bonding_curve.to_account_info(),
+            &mut self.withdraw_destination,
+            lamports,
+        )?;
+
+        //  the price held until now still counts towards the TWAP, the zeroed curve has no price afterwards
+        let clock = Clock::get()?;
+        self.price_oracle.update_price_cumulative(PriceOracle::calc_spot_price(bonding_curve, None)?, clock.unix_timestamp);
+        bonding_curve.update_reserves(0, 0, 0);
+        bonding_curve.is_withdrawn = true;
+
+        emit_cpi_event(&self.event_authority, bump_event_authority, &CurveWithdrawnEvent {
+            mint: self.token_mint.key(),
+            destination: self.withdraw_destination.key(),
+            lamports,
+            tokens,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        })?;
+
+        Ok(())
+    }
+}
@@ -16,15 +16,201 @@ declare_id!("9skK6yTzZgPmkYwwdwsVw9GzFkjHd9pVUnKJWsGYpv7m");
 pub mod fair_launch_solana_program {
     use super::*;
 
-    pub fn configure(ctx: Context<Configure>, new_config: Config) -> Result<()> {
-        ctx.accounts.handle(new_config)
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_recipient: Pubkey,
+        withdraw_destination: Pubkey,
+        buy_fee_bps: u16,
+        sell_fee_bps: u16,
+        launch_fee_lamports: u64,
+        admin_only_launch: bool,
+        curve_params: CurveParams,
+    ) -> Result<()> {
+        ctx.accounts.handle(
+            fee_recipient,
+            withdraw_destination,
+            buy_fee_bps,
+            sell_fee_bps,
+            launch_fee_lamports,
+            admin_only_launch,
+            curve_params,
+        )
     }
 
-    pub fn launch(ctx: Context<Launch>, name: String, symbol: String, uri: String) -> Result<()> {
-        ctx.accounts.handle(name, symbol, uri, ctx.bumps.global_config)
+    pub fn update_fees(
+        ctx: Context<UpdateFees>,
+        buy_fee_bps: u16,
+        sell_fee_bps: u16,
+        creator_fee_bps: u16,
+        referral_fee_bps: u16,
+        launch_fee_lamports: u64,
+    ) -> Result<()> {
+        ctx.accounts.handle(
+            buy_fee_bps,
+            sell_fee_bps,
+            creator_fee_bps,
+            referral_fee_bps,
+            launch_fee_lamports,
+            ctx.bumps.event_authority,
+        )
     }
 
-    pub fn swap(ctx: Context<Swap>, amount_in: u64, is_buy: bool) -> Result<()> {
-        ctx.accounts.handle(amount_in, is_buy, ctx.bumps.bonding_curve)
+    pub fn update_fee_recipient(ctx: Context<UpdateFeeRecipient>, fee_recipient: Pubkey, withdraw_destination: Pubkey) -> Result<()> {
+        ctx.accounts.handle(fee_recipient, withdraw_destination, ctx.bumps.event_authority)
+    }
+
+    //  fee share recipients are passed as remaining accounts, in the order of `fee_shares`
+    pub fn update_fee_shares<'info>(ctx: Context<'_, '_, '_, 'info, UpdateFeeShares<'info>>, fee_shares: Vec<FeeShare>) -> Result<()> {
+        ctx.accounts.handle(fee_shares, ctx.bumps.event_authority, ctx.remaining_accounts)
+    }
+
+    pub fn update_fee_tiers(ctx: Context<UpdateFeeTiers>, fee_tiers: Vec<FeeTier>) -> Result<()> {
+        ctx.accounts.handle(fee_tiers, ctx.bumps.event_authority)
+    }
+
+    pub fn update_curve_params(ctx: Context<UpdateCurveParams>, curve_params: CurveParams) -> Result<()> {
+        ctx.accounts.handle(curve_params, ctx.bumps.event_authority)
+    }
+
+    pub fn update_launch_access(ctx: Context<UpdateLaunchAccess>, admin_only_launch: bool) -> Result<()> {
+        ctx.accounts.handle(admin_only_launch, ctx.bumps.event_authority)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        ctx.accounts.handle()
+    }
+
+    pub fn migrate_curve(ctx: Context<MigrateCurve>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.price_oracle)
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.handle(new_authority, ctx.bumps.event_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.event_authority)
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        ctx.accounts.handle(paused, ctx.bumps.event_authority)
+    }
+
+    pub fn set_curve_paused(ctx: Context<SetCurvePaused>, paused: bool) -> Result<()> {
+        ctx.accounts.handle(paused, ctx.bumps.event_authority)
+    }
+
+    //  fee share recipients are passed as remaining accounts when the creator buys at launch
+    #[allow(clippy::too_many_arguments)]
+    pub fn launch<'info>(
+        ctx: Context<'_, '_, '_, 'info, Launch<'info>>,
+        name: String,
+        symbol: String,
+        uri: String,
+        expires_at: Option<i64>,
+        virtual_lamport_reserves: Option<u64>,
+        virtual_token_reserves: Option<u64>,
+        vesting: Option<VestingParams>,
+        initial_buy_lamports: Option<u64>,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.handle(
+            name,
+            symbol,
+            uri,
+            expires_at,
+            virtual_lamport_reserves,
+            virtual_token_reserves,
+            vesting,
+            initial_buy_lamports,
+            min_amount_out,
+            ctx.bumps.global_config,
+            ctx.bumps.bonding_curve,
+            ctx.bumps.vesting,
+            ctx.bumps.price_oracle,
+            ctx.bumps.trader_stats,
+            ctx.bumps.event_authority,
+            ctx.remaining_accounts,
+        )
+    }
+
+    //  fee share recipients are passed as remaining accounts
+    pub fn swap<'info>(ctx: Context<'_, '_, '_, 'info, Swap<'info>>, amount_in: u64, is_buy: bool, min_amount_out: u64) -> Result<()> {
+        ctx.accounts.handle(
+            amount_in,
+            is_buy,
+            min_amount_out,
+            ctx.bumps.bonding_curve,
+            ctx.bumps.trader_stats,
+            ctx.bumps.event_authority,
+            ctx.remaining_accounts,
+        )
+    }
+
+    pub fn swap_exact_out<'info>(ctx: Context<'_, '_, '_, 'info, Swap<'info>>, amount_out: u64, is_buy: bool, max_amount_in: u64) -> Result<()> {
+        ctx.accounts.handle_exact_out(
+            amount_out,
+            is_buy,
+            max_amount_in,
+            ctx.bumps.bonding_curve,
+            ctx.bumps.trader_stats,
+            ctx.bumps.event_authority,
+            ctx.remaining_accounts,
+        )
+    }
+
+    //  read-only, the quote is returned to the caller through the transaction return data
+    pub fn quote(ctx: Context<Quote>, amount_in: u64, is_buy: bool) -> Result<SwapQuote> {
+        ctx.accounts.handle(amount_in, is_buy)
+    }
+
+    //  read-only, the TWAP is returned to the caller through the transaction return data, migrated curves also pass their pool
+    pub fn twap(ctx: Context<Twap>, window_seconds: i64) -> Result<u64> {
+        ctx.accounts.handle(window_seconds)
+    }
+
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.bonding_curve, ctx.bumps.pool, ctx.bumps.event_authority)
+    }
+
+    pub fn pool_swap(ctx: Context<PoolSwap>, amount_in: u64, is_buy: bool, min_amount_out: u64) -> Result<()> {
+        ctx.accounts.handle(amount_in, is_buy, min_amount_out, ctx.bumps.event_authority)
+    }
+
+    pub fn pool_deposit(ctx: Context<PoolDeposit>, lp_amount: u64, max_lamports_in: u64, max_tokens_in: u64) -> Result<()> {
+        ctx.accounts.handle(lp_amount, max_lamports_in, max_tokens_in, ctx.bumps.event_authority)
+    }
+
+    pub fn pool_withdraw(ctx: Context<PoolWithdraw>, lp_amount: u64, min_lamports_out: u64, min_tokens_out: u64) -> Result<()> {
+        ctx.accounts.handle(lp_amount, min_lamports_out, min_tokens_out, ctx.bumps.event_authority)
+    }
+
+    pub fn withdraw_completed(ctx: Context<WithdrawCompleted>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.bonding_curve, ctx.bumps.event_authority)
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.event_authority)
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.event_authority)
+    }
+
+    pub fn burn_reserved_tokens(ctx: Context<BurnReservedTokens>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.event_authority)
+    }
+
+    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.event_authority)
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.referrer)
+    }
+
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        ctx.accounts.handle(ctx.bumps.event_authority)
     }
 }
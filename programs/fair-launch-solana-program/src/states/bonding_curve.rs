@@ -1,18 +1,101 @@
-use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
+use anchor_lang::{prelude::*, Discriminator};
+use crate::constants::{BONDING_CURVE_VERSION, BPS_DENOMINATOR, LEGACY_LAYOUT_VERSION, PRICE_PRECISION, TOKEN_DECIMAL};
 use crate::errors::CustomError;
-use crate::events::TradeEvent;
-use crate::utils::{sol_transfer_from_user, sol_transfer_from_curve, token_transfer_from_pda, token_transfer_from_user};
+use crate::events::{CreatorFeeAccruedEvent, TradeEvent};
+use crate::states::{Config, FeeShare, Referrer, TraderStats};
+use crate::utils::{amount_before_fee, emit_cpi_event, fee_ceil, mul_div_ceil, mul_div_floor, to_u64, sol_transfer_from_user, sol_transfer_from_pda, stored_layout_version, token_transfer_from_pda, token_transfer_from_user};
+
+//  which side of a swap is fixed by the trader
+#[derive(Clone, Copy)]
+pub enum SwapAmount {
+    //  spend exactly `amount_in`, receive at least `min_amount_out`
+    ExactIn { amount_in: u64, min_amount_out: u64 },
+    //  receive exactly `amount_out`, spend at most `max_amount_in`
+    ExactOut { amount_out: u64, max_amount_in: u64 },
+}
 
+//  result of simulating a swap, returned by the `quote` instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_lamports: u64,
+    pub virtual_token_reserves: u64,
+    pub virtual_lamport_reserves: u64,
+    pub actual_lamport_reserves: u64,
+    pub spot_price: u64,
+    pub price_impact_bps: u64,
+}
+
+//  new fields take their bytes from `reserved` and must be valid when zeroed,
+//  the account size never changes, `migrate_curve` only upgrades legacy accounts
 #[account]
+#[derive(InitSpace)]
 pub struct BondingCurve {
+    pub version: u8,
+    pub creator: Pubkey,
     pub virtual_token_reserves: u64,
     pub virtual_lamport_reserves: u64,
     pub actual_lamport_reserves: u64,
     pub is_completed: bool,
+    pub is_migrated: bool,
+    pub is_withdrawn: bool,
+    pub expires_at: i64, //  unix timestamp after which an incomplete curve switches to refunds, 0 if it never expires
+    pub reserved_tokens: u64, //  tokens minted outside the curve at launch, they have no claim on refunds
+    pub trading_paused: bool, //  halts trading on this curve only
+    pub creator_fee_lamports: u64, //  unclaimed creator fees, held by the curve next to its reserves
+    pub trade_count: u64, //  number of trades on this curve, the last one was emitted with `sequence == trade_count`
+    pub reserved: [u8; 48],
+}
+
+//  layout of curve accounts created before the layout was versioned
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyBondingCurve {
+    pub virtual_token_reserves: u64,
+    pub virtual_lamport_reserves: u64,
+    pub actual_lamport_reserves: u64,
+    pub is_completed: bool,
+}
+
+impl LegacyBondingCurve {
+    pub const LEN: usize = 8 + std::mem::size_of::<LegacyBondingCurve>();
 }
 
 impl<'info> BondingCurve {
+    //  legacy curves were admin launches without refunds, vesting or a recorded creator
+    pub fn from_legacy(legacy_curve: LegacyBondingCurve) -> BondingCurve {
+        BondingCurve {
+            version: BONDING_CURVE_VERSION,
+            creator: Pubkey::default(),
+            virtual_token_reserves: legacy_curve.virtual_token_reserves,
+            virtual_lamport_reserves: legacy_curve.virtual_lamport_reserves,
+            actual_lamport_reserves: legacy_curve.actual_lamport_reserves,
+            is_completed: legacy_curve.is_completed,
+            is_migrated: false,
+            is_withdrawn: false,
+            expires_at: 0,
+            reserved_tokens: 0,
+            trading_paused: false,
+            creator_fee_lamports: 0,
+            trade_count: 0,
+            reserved: [0; 48],
+        }
+    }
+
+    //  data of a curve account rewritten to the current layout, picked by its stored version
+    pub fn migrate_data(data: &[u8]) -> Result<Vec<u8>> {
+        let bonding_curve = match stored_layout_version(data, &BondingCurve::DISCRIMINATOR, LegacyBondingCurve::LEN)? {
+            LEGACY_LAYOUT_VERSION => BondingCurve::from_legacy(LegacyBondingCurve::deserialize(&mut &data[8..])?),
+            BONDING_CURVE_VERSION => return err!(CustomError::AccountAlreadyMigrated),
+            _ => return err!(CustomError::UnsupportedLayoutVersion),
+        };
+
+        let mut new_data = Vec::with_capacity(8 + BondingCurve::INIT_SPACE);
+        bonding_curve.try_serialize(&mut new_data)?;
+
+        Ok(new_data)
+    }
+
     pub fn get_signer<'a>(mint: &'a Pubkey, bump: &'a u8) -> [&'a [u8]; 2] {
         [
             mint.as_ref(),
@@ -20,6 +103,20 @@ impl<'info> BondingCurve {
         ]
     }
 
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at && !self.is_completed
+    }
+
+    //  lamports refunded for burning `token_amount` out of `circulating_tokens` on an expired curve
+    //  only actual reserves are shared out, the virtual lamports never existed
+    pub fn calc_refund(&self, token_amount: u64, circulating_tokens: u64) -> Result<u64> {
+        to_u64(mul_div_floor(
+            self.actual_lamport_reserves as u128,
+            token_amount as u128,
+            circulating_tokens as u128,
+        )?)
+    }
+
     pub fn update_reserves(&mut self, virtual_lamport_reserves: u64, virtual_token_reserves: u64, actual_lamport_reserves: u64) {
         self.virtual_lamport_reserves = virtual_lamport_reserves;
         self.virtual_token_reserves = virtual_token_reserves;
@@ -27,105 +124,455 @@ impl<'info> BondingCurve {
     }
 
     //  calculate amount out and fee lamports
+    //  rounding always favours the curve: amounts out round down, fees round up
     fn calc_amount_out(
-        &mut self,
+        &self,
         amount_in: u64,
         is_buy: bool,
-        fee_percent: f64,
+        fee_bps: u16,
     ) -> Result<(u64, u64)> {
-        let buy_fee = if is_buy {
-            (amount_in as f64 * fee_percent / 100.0) as u64
+        let x = self.virtual_token_reserves as u128;
+        let y = self.virtual_lamport_reserves as u128;
+
+        if is_buy {
+            // Buying: fee is taken from the SOL paid in, the rest goes to the curve
+            let fee_lamports = fee_ceil(amount_in, fee_bps)?;
+            let amount_in_after_fee = amount_in
+                .checked_sub(fee_lamports)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)? as u128;
+
+            // x - k / (y + dy) == x * dy / (y + dy)
+            let new_y = y
+                .checked_add(amount_in_after_fee)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+            let amount_out = to_u64(mul_div_floor(x, amount_in_after_fee, new_y)?)?;
+
+            Ok((amount_out, fee_lamports))
         } else {
-            0
-        };
-        let amount_in_after_fee = amount_in - buy_fee;
+            // Selling: fee is taken from the SOL paid out by the curve
+            // y - k / (x + dx) == y * dx / (x + dx)
+            let new_x = x
+                .checked_add(amount_in as u128)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+            let amount_out = to_u64(mul_div_floor(y, amount_in as u128, new_x)?)?;
+            let fee_lamports = fee_ceil(amount_out, fee_bps)?;
+
+            Ok((amount_out, fee_lamports))
+        }
+    }
+
+    //  calculate amount in and fee lamports needed to receive exactly `amount_out`
+    //  inverse of `calc_amount_out`, amounts in and fees round up
+    fn calc_amount_in(
+        &self,
+        amount_out: u64,
+        is_buy: bool,
+        fee_bps: u16,
+    ) -> Result<(u64, u64)> {
+        let x = self.virtual_token_reserves as u128;
+        let y = self.virtual_lamport_reserves as u128;
+
+        if is_buy {
+            // Buying: dy = y * dx / (x - dx), then grossed up by the buy fee
+            let new_x = x
+                .checked_sub(amount_out as u128)
+                .filter(|new_x| *new_x > 0)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+            let amount_in_after_fee = to_u64(mul_div_ceil(y, amount_out as u128, new_x)?)?;
+            let amount_in = amount_before_fee(amount_in_after_fee, fee_bps)?;
+
+            Ok((amount_in, amount_in - amount_in_after_fee))
+        } else {
+            // Selling: `amount_out` is net of the sell fee, the curve pays out the gross amount
+            let amount_out_before_fee = amount_before_fee(amount_out, fee_bps)?;
+            let new_y = y
+                .checked_sub(amount_out_before_fee as u128)
+                .filter(|new_y| *new_y > 0)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+            let amount_in = to_u64(mul_div_ceil(x, amount_out_before_fee as u128, new_y)?)?;
+
+            Ok((amount_in, amount_out_before_fee - amount_out))
+        }
+    }
 
-        let x = self.virtual_token_reserves as f64;
-        let y = self.virtual_lamport_reserves as f64;
-        let k = x * y;
+    //  resolve a swap into (amount in, amount out, fee lamports) and enforce the trader limits
+    //  for sells the amount out is the gross lamports leaving the curve, fee included
+    fn calc_swap(
+        &self,
+        amount: SwapAmount,
+        is_buy: bool,
+        fee_bps: u16,
+        lamports_needed_to_complete_curve: u64,
+    ) -> Result<(u64, u64, u64)> {
+        let (amount_in, amount_out, fee_lamports) = match amount {
+            SwapAmount::ExactIn { amount_in, .. } => {
+                let (amount_out, fee_lamports) = self.calc_amount_out(amount_in, is_buy, fee_bps)?;
+                (amount_in, amount_out, fee_lamports)
+            }
+            SwapAmount::ExactOut { amount_out, .. } => {
+                let (amount_in, fee_lamports) = self.calc_amount_in(amount_out, is_buy, fee_bps)?;
+                let amount_out = if is_buy {
+                    amount_out
+                } else {
+                    amount_out
+                        .checked_add(fee_lamports)
+                        .ok_or(CustomError::OverflowOrUnderflowOccurred)?
+                };
+                (amount_in, amount_out, fee_lamports)
+            }
+        };
 
-        let amount_out: f64 = if is_buy {
-            // Buying: Compute tokens to receive for `amount_in_after_fee` SOL
-            x - (k / (y + amount_in_after_fee as f64))
+        //  the exact in buy that crosses the completion threshold is only filled up to the threshold,
+        //  the remainder is never taken from the trader
+        //  an exact out buy cannot be filled partially, it is rejected instead
+        let amount_in_after_fee = amount_in
+            .checked_sub(fee_lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let lamports_to_complete = lamports_needed_to_complete_curve.saturating_sub(self.actual_lamport_reserves);
+        let crosses_completion = is_buy && amount_in_after_fee > lamports_to_complete;
+        require!(
+            !(crosses_completion && matches!(amount, SwapAmount::ExactOut { .. })),
+            CustomError::AmountOutTooLarge
+        );
+        let (filled_in, filled_out, filled_fee) = if crosses_completion {
+            //  exactly the missing lamports enter the curve, fee rounding stays with the fee
+            let filled_in = amount_before_fee(lamports_to_complete, fee_bps)?;
+            let new_y = (self.virtual_lamport_reserves as u128)
+                .checked_add(lamports_to_complete as u128)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+            let filled_out = to_u64(mul_div_floor(
+                self.virtual_token_reserves as u128,
+                lamports_to_complete as u128,
+                new_y,
+            )?)?;
+            (filled_in, filled_out, filled_in - lamports_to_complete)
         } else {
-            // Selling: Compute SOL to receive for `amount_in_after_fee` tokens
-            y - (k / (x + amount_in as f64))
+            (amount_in, amount_out, fee_lamports)
         };
 
-        // Ensure non-negative values and convert to u64
-        let amount_out = amount_out.max(0.0) as u64;
+        //  slippage protection
+        match amount {
+            SwapAmount::ExactIn { min_amount_out, .. } => {
+                let received = if is_buy {
+                    filled_out
+                } else {
+                    filled_out
+                        .checked_sub(filled_fee)
+                        .ok_or(CustomError::OverflowOrUnderflowOccurred)?
+                };
+                //  a partial fill only has to honour the limit pro rata
+                let min_amount_out = if filled_in < amount_in {
+                    to_u64(mul_div_ceil(min_amount_out as u128, filled_in as u128, amount_in as u128)?)?
+                } else {
+                    min_amount_out
+                };
+
+                require!(
+                    received >= min_amount_out,
+                    CustomError::ReturnAmountTooSmall
+                );
+            }
+            SwapAmount::ExactOut { max_amount_in, .. } => {
+                require!(
+                    filled_in <= max_amount_in,
+                    CustomError::AmountInTooLarge
+                );
+            }
+        }
+
+        Ok((filled_in, filled_out, filled_fee))
+    }
+
+    //  reserves after a swap resolved by `calc_swap`, as (virtual lamports, virtual tokens, actual lamports)
+    fn calc_reserves_after(
+        &self,
+        is_buy: bool,
+        amount_in: u64,
+        amount_out: u64,
+        fee_lamports: u64,
+    ) -> Result<(u64, u64, u64)> {
+        if is_buy {
+            //  only the lamports left after the fee enter the curve
+            let amount_in_after_fees = amount_in
+                .checked_sub(fee_lamports)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+            let new_token_reserves = self
+                .virtual_token_reserves
+                .checked_sub(amount_out)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+            let new_virtual_sol_reserves = self
+                .virtual_lamport_reserves
+                .checked_add(amount_in_after_fees)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+            let new_actual_sol_reserves = self
+                .actual_lamport_reserves
+                .checked_add(amount_in_after_fees)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+            Ok((new_virtual_sol_reserves, new_token_reserves, new_actual_sol_reserves))
+        } else {
+            //  the gross amount out, fee included, leaves the curve
+            let new_virtual_token_reserves = self
+                .virtual_token_reserves
+                .checked_add(amount_in)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+            let new_virtual_lamport_reserves = self
+                .virtual_lamport_reserves
+                .checked_sub(amount_out)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+            let new_actual_lamport_reserves = self
+                .actual_lamport_reserves
+                .checked_sub(amount_out)
+                .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+            Ok((new_virtual_lamport_reserves, new_virtual_token_reserves, new_actual_lamport_reserves))
+        }
+    }
+
+    //  spot price in lamports per token unit, scaled by `PRICE_PRECISION`
+    //  withdrawn curves are zeroed and have no price anymore
+    pub fn calc_spot_price(virtual_lamport_reserves: u64, virtual_token_reserves: u64) -> Result<u64> {
+        require!(virtual_token_reserves > 0, CustomError::CurveHasNoLiquidity);
+
+        to_u64(mul_div_floor(
+            virtual_lamport_reserves as u128,
+            PRICE_PRECISION,
+            virtual_token_reserves as u128,
+        )?)
+    }
 
-        let sell_fee = (amount_out as f64 * fee_percent / 100.0) as u64;
-        let fees =  if is_buy { buy_fee } else { sell_fee };
+    //  curves without a recorded creator, like migrated legacy curves, charge no creator fee
+    fn creator_fee_bps(&self, creator_fee_bps: u16) -> u16 {
+        if self.creator == Pubkey::default() {
+            0
+        } else {
+            creator_fee_bps
+        }
+    }
 
-        Ok((amount_out, fees))
+    //  creator part of a fee charged at `fee_bps + creator_fee_bps`, rounded down so the protocol keeps the dust
+    fn calc_creator_fee(fee_lamports: u64, fee_bps: u16, creator_fee_bps: u16) -> Result<u64> {
+        if creator_fee_bps == 0 {
+            return Ok(0);
+        }
+
+        to_u64(mul_div_floor(
+            fee_lamports as u128,
+            creator_fee_bps as u128,
+            fee_bps as u128 + creator_fee_bps as u128,
+        )?)
     }
 
+    //  referral part of the protocol fee, nothing without a referrer
+    fn calc_referral_fee(
+        protocol_fee_lamports: u64,
+        referrer: &Option<&mut Account<'info, Referrer>>,
+        referral_fee_bps: u16,
+    ) -> Result<u64> {
+        if referrer.is_none() {
+            return Ok(0);
+        }
+
+        to_u64(mul_div_floor(
+            protocol_fee_lamports as u128,
+            referral_fee_bps as u128,
+            BPS_DENOMINATOR as u128,
+        )?)
+    }
+
+    //  sequence number of the trade being recorded, lets indexers order trades within a slot
+    fn next_trade_sequence(&mut self) -> Result<u64> {
+        self.trade_count = self
+            .trade_count
+            .checked_add(1)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+        Ok(self.trade_count)
+    }
+
+    fn accrue_creator_fee(
+        &mut self,
+        token_mint: &Pubkey,
+        creator_fee_lamports: u64,
+        event_authority: &AccountInfo,
+        event_authority_bump: u8,
+    ) -> Result<()> {
+        if creator_fee_lamports == 0 {
+            return Ok(());
+        }
+
+        self.creator_fee_lamports = self
+            .creator_fee_lamports
+            .checked_add(creator_fee_lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+        emit_cpi_event(event_authority, event_authority_bump, &CreatorFeeAccruedEvent {
+            mint: *token_mint,
+            creator: self.creator,
+            lamports: creator_fee_lamports,
+            accrued_lamports: self.creator_fee_lamports,
+        })?;
+
+        Ok(())
+    }
+
+    //  fees of a trade in basis points: the fee of the trader's volume tier and the creator fee,
+    //  traders without stats pay the base fee
+    fn calc_trade_fee_bps(&self, global_config: &Config, is_buy: bool, trader_stats: Option<&TraderStats>) -> (u16, u16) {
+        let volume_lamports = trader_stats.map_or(0, |trader_stats| trader_stats.volume_lamports);
+
+        (
+            global_config.trader_fee_bps(is_buy, volume_lamports),
+            self.creator_fee_bps(global_config.creator_fee_bps),
+        )
+    }
+
+    //  simulate a swap without moving any funds, priced like `buy` and `sell` for the same trader
+    pub fn quote(
+        &self,
+        amount: SwapAmount,
+        is_buy: bool,
+        global_config: &Config,
+        trader_stats: Option<&TraderStats>,
+    ) -> Result<SwapQuote> {
+        let (fee_bps, creator_fee_bps) = self.calc_trade_fee_bps(global_config, is_buy, trader_stats);
+        let (amount_in, amount_out, fee_lamports) = self.calc_swap(
+            amount,
+            is_buy,
+            fee_bps + creator_fee_bps,
+            global_config.lamports_needed_to_complete_curve,
+        )?;
+        let (virtual_lamport_reserves, virtual_token_reserves, actual_lamport_reserves) =
+            self.calc_reserves_after(is_buy, amount_in, amount_out, fee_lamports)?;
+
+        let spot_price_before = Self::calc_spot_price(self.virtual_lamport_reserves, self.virtual_token_reserves)?;
+        let spot_price = Self::calc_spot_price(virtual_lamport_reserves, virtual_token_reserves)?;
+        let price_impact_bps = to_u64(mul_div_ceil(
+            spot_price.abs_diff(spot_price_before) as u128,
+            BPS_DENOMINATOR as u128,
+            spot_price_before as u128,
+        )?)?;
+
+        //  report what the trader actually receives, sells are net of the fee
+        let amount_out = if is_buy { amount_out } else { amount_out - fee_lamports };
+
+        Ok(SwapQuote {
+            amount_in,
+            amount_out,
+            fee_lamports,
+            virtual_token_reserves,
+            virtual_lamport_reserves,
+            actual_lamport_reserves,
+            spot_price,
+            price_impact_bps,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn buy(
         &mut self,
-        token_mint: &Account<'info, Mint>, //  token mint address
-        lamports_needed_to_complete_curve: u64, //  bonding curve limit
+        token_mint: &AccountInfo<'info>, //  token mint address
         user: &Signer<'info>, //  user address
 
         bonding_curve_pda: &mut AccountInfo<'info>, //  bonding curve PDA
         fee_recipient: &mut AccountInfo<'info>, //  team wallet address to get fee
+        fee_share_accounts: &[AccountInfo<'info>], //  fee share recipients, validated against the config
+        referrer: Option<&mut Account<'info, Referrer>>, //  referrer of the trader, gets a share of the protocol fee
+        trader_stats: Option<&mut Account<'info, TraderStats>>, //  volume of the trader, feeds the fee tiers
 
         user_ata: &mut AccountInfo<'info>, //  associated toke accounts for user
         curve_ata: &mut AccountInfo<'info>, //  associated toke accounts for curve
 
-        amount_in: u64, //  sol amount to pay
-        fee_percent: f64, //  buy fee
+        amount: SwapAmount, //  sol amount to pay or token amount to receive
+        global_config: &Config, //  fee schedule, fee shares and completion threshold
 
         curve_bump: u8, // bump for signer
 
         system_program: &AccountInfo<'info>, //  system program
         token_program: &AccountInfo<'info>,  //  token program
-    ) -> Result<bool> {
 
-        let (amount_out, fee_lamports) =
-            self.calc_amount_out(amount_in, true, fee_percent)?;
-        let amount_in_after_fees = amount_in - fee_lamports;
+        event_authority: &AccountInfo<'info>, //  signs the event self-CPIs
+        event_authority_bump: u8,
+    ) -> Result<bool> {
 
-        //  transfer fee to team wallet
-        sol_transfer_from_user(&user, fee_recipient, system_program, fee_lamports)?;
+        let lamports_needed_to_complete_curve = global_config.lamports_needed_to_complete_curve;
+        let (fee_bps, creator_fee_bps) = self.calc_trade_fee_bps(global_config, true, trader_stats.as_deref().map(|trader_stats| &**trader_stats));
+        let (amount_in, amount_out, fee_lamports) =
+            self.calc_swap(amount, true, fee_bps + creator_fee_bps, lamports_needed_to_complete_curve)?;
+        let amount_in_after_fees = amount_in
+            .checked_sub(fee_lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let creator_fee_lamports = Self::calc_creator_fee(fee_lamports, fee_bps, creator_fee_bps)?;
+        let referral_fee_lamports = Self::calc_referral_fee(fee_lamports - creator_fee_lamports, &referrer, global_config.referral_fee_bps)?;
+        let protocol_fee_lamports = fee_lamports - creator_fee_lamports - referral_fee_lamports;
+
+        //  transfer fee shares, then the rest of the protocol fee to team wallet
+        let mut fee_lamports_left = protocol_fee_lamports;
+        for (account, share_lamports) in fee_share_accounts.iter().zip(FeeShare::calc_split(global_config.active_fee_shares(), protocol_fee_lamports)?) {
+            sol_transfer_from_user(user, account, system_program, share_lamports)?;
+            fee_lamports_left -= share_lamports;
+        }
+        sol_transfer_from_user(user, fee_recipient, system_program, fee_lamports_left)?;
+        //  the creator fee is kept by the curve until claimed
+        sol_transfer_from_user(user, bonding_curve_pda, system_program, creator_fee_lamports)?;
+        //  the referral fee is kept by the referrer account until claimed
+        let referrer = match referrer {
+            Some(referrer) => {
+                sol_transfer_from_user(user, &referrer.to_account_info(), system_program, referral_fee_lamports)?;
+                referrer.accrue(referral_fee_lamports)?;
+                Some(referrer.wallet)
+            }
+            None => None,
+        };
         //  transfer adjusted amount to curve
-        sol_transfer_from_user(&user, bonding_curve_pda, system_program, amount_in_after_fees)?;
+        sol_transfer_from_user(user, bonding_curve_pda, system_program, amount_in_after_fees)?;
         //  transfer token from PDA to user
         token_transfer_from_pda(
             curve_ata,
             bonding_curve_pda,
             user_ata,
+            token_mint,
             token_program,
             &[&BondingCurve::get_signer(&token_mint.key(), &curve_bump)],
             amount_out,
+            TOKEN_DECIMAL,
         )?;
 
         //  calculate new reserves
-        let new_token_reserves = self
-            .virtual_token_reserves
-            .checked_sub(amount_out)
-            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
-
-        let new_virtual_sol_reserves = self
-            .virtual_lamport_reserves
-            .checked_add(amount_in_after_fees)
-            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
-
-        let new_actual_sol_reserves = self
-            .actual_lamport_reserves
-            .checked_add(amount_in_after_fees)
-            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let (new_virtual_sol_reserves, new_token_reserves, new_actual_sol_reserves) =
+            self.calc_reserves_after(true, amount_in, amount_out, fee_lamports)?;
 
         //  update reserves on the curve
         self.update_reserves(new_virtual_sol_reserves, new_token_reserves, new_actual_sol_reserves);
+        self.accrue_creator_fee(&token_mint.key(), creator_fee_lamports, event_authority, event_authority_bump)?;
+        if let Some(trader_stats) = trader_stats {
+            trader_stats.record_trade(amount_in)?;
+        }
 
-        emit!(TradeEvent {
+        let clock = Clock::get()?;
+        emit_cpi_event(event_authority, event_authority_bump, &TradeEvent {
             trader: user.key(),
             asset: token_mint.key(),
             amount_in,
             amount_out,
             is_buy: true,
-        });
+            referrer,
+            referral_fee_lamports,
+            fee_lamports,
+            creator_fee_lamports,
+            virtual_lamport_reserves: new_virtual_sol_reserves,
+            virtual_token_reserves: new_token_reserves,
+            actual_lamport_reserves: new_actual_sol_reserves,
+            spot_price: Self::calc_spot_price(new_virtual_sol_reserves, new_token_reserves)?,
+            sequence: self.next_trade_sequence()?,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        })?;
 
         //  return true if the curve reached the limit
         if new_actual_sol_reserves >= lamports_needed_to_complete_curve {
@@ -137,72 +584,288 @@ impl<'info> BondingCurve {
         Ok(false)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn sell(
         &mut self,
-        token_mint: &Account<'info, Mint>, //  token mint address
+        token_mint: &AccountInfo<'info>, //  token mint address
         user: &mut AccountInfo<'info>,              //  user address
 
         curve_pda: &mut AccountInfo<'info>, //  bonding curve PDA
         fee_recipient: &mut AccountInfo<'info>, //  team wallet address to get fee
+        fee_share_accounts: &[AccountInfo<'info>], //  fee share recipients, validated against the config
+        referrer: Option<&mut Account<'info, Referrer>>, //  referrer of the trader, gets a share of the protocol fee
+        trader_stats: Option<&mut Account<'info, TraderStats>>, //  volume of the trader, feeds the fee tiers
 
         user_ata: &mut AccountInfo<'info>, //  associated toke accounts for user
         curve_ata: &mut AccountInfo<'info>, //  associated toke accounts for curve
 
-        amount_in: u64,      //  token amount to sell
-        fee_percent: f64,    //  sell fee
+        amount: SwapAmount,  //  token amount to sell or lamports to receive after fees
+        global_config: &Config, //  fee schedule and fee shares
 
         token_program: &AccountInfo<'info>,  //  token program
+
+        event_authority: &AccountInfo<'info>, //  signs the event self-CPIs
+        event_authority_bump: u8,
     ) -> Result<()> {
-        let (amount_out, fee_lamports) =
-            self.calc_amount_out(amount_in, false, fee_percent)?;
+        let (fee_bps, creator_fee_bps) = self.calc_trade_fee_bps(global_config, false, trader_stats.as_deref().map(|trader_stats| &**trader_stats));
+        let (amount_in, amount_out, fee_lamports) =
+            self.calc_swap(amount, false, fee_bps + creator_fee_bps, u64::MAX)?; //  sells never complete the curve
+        let amount_out_after_fees = amount_out
+            .checked_sub(fee_lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let creator_fee_lamports = Self::calc_creator_fee(fee_lamports, fee_bps, creator_fee_bps)?;
+        let referral_fee_lamports = Self::calc_referral_fee(fee_lamports - creator_fee_lamports, &referrer, global_config.referral_fee_bps)?;
+        let protocol_fee_lamports = fee_lamports - creator_fee_lamports - referral_fee_lamports;
 
         //  transfer token from user to PDA
         token_transfer_from_user(
             user_ata,
             user,
             curve_ata,
+            token_mint,
             token_program,
             amount_in,
+            TOKEN_DECIMAL,
         )?;
-        //  transfer fee to team wallet
-        sol_transfer_from_curve(
+        //  transfer fee shares, then the rest of the protocol fee to team wallet, the creator fee stays on the curve
+        let mut fee_lamports_left = protocol_fee_lamports;
+        for (account, share_lamports) in fee_share_accounts.iter().zip(FeeShare::calc_split(global_config.active_fee_shares(), protocol_fee_lamports)?) {
+            sol_transfer_from_pda(curve_pda, &mut account.clone(), share_lamports)?;
+            fee_lamports_left -= share_lamports;
+        }
+        sol_transfer_from_pda(
             curve_pda,
             fee_recipient,
-            fee_lamports,
+            fee_lamports_left,
         )?;
+        let referrer = match referrer {
+            Some(referrer) => {
+                sol_transfer_from_pda(curve_pda, &mut referrer.to_account_info(), referral_fee_lamports)?;
+                referrer.accrue(referral_fee_lamports)?;
+                Some(referrer.wallet)
+            }
+            None => None,
+        };
         //  transfer SOL to user
-        sol_transfer_from_curve(
+        sol_transfer_from_pda(
             curve_pda,
             user,
-            amount_out - fee_lamports,
+            amount_out_after_fees,
         )?;
 
         //  calculate new reserves
-        let new_virtual_token_reserves = self
-            .virtual_token_reserves
-            .checked_add(amount_in)
-            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        let (new_virtual_lamport_reserves, new_virtual_token_reserves, new_actual_lamport_reserves) =
+            self.calc_reserves_after(false, amount_in, amount_out, fee_lamports)?;
 
-        let new_virtual_lamport_reserves = self
-            .virtual_lamport_reserves
-            .checked_sub(amount_out + fee_lamports)
-            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
-
-        let new_actual_lamport_reserves = self
-            .actual_lamport_reserves
-            .checked_sub(amount_out)
-            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        //  update reserves on the curve
+        self.update_reserves(new_virtual_lamport_reserves, new_virtual_token_reserves, new_actual_lamport_reserves);
+        self.accrue_creator_fee(&token_mint.key(), creator_fee_lamports, event_authority, event_authority_bump)?;
+        if let Some(trader_stats) = trader_stats {
+            trader_stats.record_trade(amount_out)?;
+        }
 
-        emit!(TradeEvent {
+        let clock = Clock::get()?;
+        emit_cpi_event(event_authority, event_authority_bump, &TradeEvent {
             trader: user.key(),
             asset: token_mint.key(),
             amount_in,
-            amount_out,
+            amount_out: amount_out_after_fees,
             is_buy: false,
-        });
-        //  update reserves on the curve
-        self.update_reserves(new_virtual_lamport_reserves, new_virtual_token_reserves, new_actual_lamport_reserves);
+            referrer,
+            referral_fee_lamports,
+            fee_lamports,
+            creator_fee_lamports,
+            virtual_lamport_reserves: new_virtual_lamport_reserves,
+            virtual_token_reserves: new_virtual_token_reserves,
+            actual_lamport_reserves: new_actual_lamport_reserves,
+            spot_price: Self::calc_spot_price(new_virtual_lamport_reserves, new_virtual_token_reserves)?,
+            sequence: self.next_trade_sequence()?,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        })?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::{FeeTier, LegacyConfig};
+    use crate::utils::calc_resize_rent;
+
+    fn curve(virtual_lamport_reserves: u64, virtual_token_reserves: u64) -> BondingCurve {
+        //  every other field zeroed
+        let mut curve = BondingCurve::deserialize(&mut &[0u8; 512][..]).unwrap();
+        curve.virtual_lamport_reserves = virtual_lamport_reserves;
+        curve.virtual_token_reserves = virtual_token_reserves;
+        curve
+    }
+
+    #[test]
+    fn exact_out_round_trips_through_exact_in() {
+        let curve = curve(100_000_000_000, 1_000_000_000_000_000);
+
+        for fee_bps in [0u16, 100, 150, 9999] {
+            for amount_out in [1u64, 1_000_000, 123_456_789_000, 500_000_000_000_000] {
+                //  buys: the amount in is the smallest one that still buys `amount_out` tokens
+                let (amount_in, _) = curve.calc_amount_in(amount_out, true, fee_bps).unwrap();
+                assert!(curve.calc_amount_out(amount_in, true, fee_bps).unwrap().0 >= amount_out);
+                assert!(curve.calc_amount_out(amount_in - 1, true, fee_bps).unwrap().0 < amount_out);
+            }
+
+            for amount_out in [1u64, 1_000, 1_000_000] {
+                //  sells: `amount_out` is net of the fee, the curve pays out the gross amount
+                let (amount_in, _) = curve.calc_amount_in(amount_out, false, fee_bps).unwrap();
+                let (gross_out, fee_lamports) = curve.calc_amount_out(amount_in, false, fee_bps).unwrap();
+                assert!(gross_out - fee_lamports >= amount_out);
+                let (gross_out, fee_lamports) = curve.calc_amount_out(amount_in - 1, false, fee_bps).unwrap();
+                assert!(gross_out - fee_lamports < amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn buy_crossing_completion_is_filled_up_to_the_threshold() {
+        let lamports_needed_to_complete_curve = 10_000_000_000;
+        let mut curve = curve(100_000_000_000, 1_000_000_000_000_000);
+        curve.actual_lamport_reserves = lamports_needed_to_complete_curve - 1_000_000_000;
+        let amount_in = 2_000_000_000;
+
+        //  only the missing lamports enter the curve, the rest of `amount_in` stays with the trader
+        let (filled_in, filled_out, filled_fee) = curve
+            .calc_swap(SwapAmount::ExactIn { amount_in, min_amount_out: 0 }, true, 100, lamports_needed_to_complete_curve)
+            .unwrap();
+        assert_eq!(filled_in - filled_fee, 1_000_000_000);
+        assert_eq!(filled_in, amount_before_fee(1_000_000_000, 100).unwrap());
+        assert!(filled_in < amount_in);
+
+        //  the limit is scaled down to the filled part of `amount_in`
+        let max_min_amount_out = to_u64(mul_div_floor(filled_out as u128, amount_in as u128, filled_in as u128).unwrap()).unwrap();
+        assert!(curve
+            .calc_swap(
+                SwapAmount::ExactIn { amount_in, min_amount_out: max_min_amount_out },
+                true,
+                100,
+                lamports_needed_to_complete_curve,
+            )
+            .is_ok());
+        let too_large_min_amount_out = to_u64(mul_div_ceil((filled_out + 1) as u128, amount_in as u128, filled_in as u128).unwrap()).unwrap();
+        assert_eq!(
+            curve
+                .calc_swap(
+                    SwapAmount::ExactIn { amount_in, min_amount_out: too_large_min_amount_out },
+                    true,
+                    100,
+                    lamports_needed_to_complete_curve,
+                )
+                .unwrap_err(),
+            CustomError::ReturnAmountTooSmall.into()
+        );
+
+        //  exact out buys are never filled partially
+        assert_eq!(
+            curve
+                .calc_swap(
+                    SwapAmount::ExactOut { amount_out: filled_out * 2, max_amount_in: u64::MAX },
+                    true,
+                    100,
+                    lamports_needed_to_complete_curve,
+                )
+                .unwrap_err(),
+            CustomError::AmountOutTooLarge.into()
+        );
+    }
+
+    #[test]
+    fn quote_is_priced_at_the_trader_fee_tier() {
+        let mut curve = curve(100_000_000_000, 1_000_000_000_000_000);
+        curve.creator = Pubkey::new_unique();
+        let mut global_config = Config::from_legacy(LegacyConfig {
+            fee_recipient: Pubkey::new_unique(),
+            lamports_needed_to_complete_curve: 85_000_000_000,
+            total_token_supply: 1_000_000_000_000_000,
+            buy_fee_percent: 1.0,
+            sell_fee_percent: 1.0,
+        })
+        .unwrap();
+        global_config.creator_fee_bps = 50;
+        global_config
+            .set_fee_tiers(vec![FeeTier { min_volume_lamports: 1_000, buy_fee_bps: 50, sell_fee_bps: 50 }])
+            .unwrap();
+        let trader_stats = |volume_lamports| TraderStats {
+            trader: Pubkey::default(),
+            volume_lamports,
+            trade_count: 0,
+            referrer: None,
+            bump: 0,
+        };
+        let fee_lamports = |trader_stats: Option<&TraderStats>| {
+            curve
+                .quote(SwapAmount::ExactIn { amount_in: 1_000_000, min_amount_out: 0 }, true, &global_config, trader_stats)
+                .unwrap()
+                .fee_lamports
+        };
+
+        //  base fee plus creator fee without stats or below the first tier, the tier fee plus creator fee above it
+        assert_eq!(fee_lamports(None), 15_000);
+        assert_eq!(fee_lamports(Some(&trader_stats(999))), 15_000);
+        assert_eq!(fee_lamports(Some(&trader_stats(1_000))), 10_000);
+    }
+
+    #[test]
+    fn exact_out_cannot_drain_the_curve() {
+        let curve = curve(100_000_000_000, 1_000_000_000_000_000);
+
+        assert!(curve.calc_amount_in(1_000_000_000_000_000, true, 100).is_err());
+        assert!(curve.calc_amount_in(100_000_000_000, false, 0).is_err());
+    }
+
+    #[test]
+    fn legacy_curve_with_reserves_only_gets_the_rent_top_up() {
+        let actual_lamport_reserves = 42_000_000_000;
+        //  legacy accounts were sized from the padded struct, the serialized fields leave zeroed bytes behind
+        let mut data = BondingCurve::DISCRIMINATOR.to_vec();
+        data.extend(
+            LegacyBondingCurve {
+                virtual_token_reserves: 600_000_000_000_000,
+                virtual_lamport_reserves: 72_000_000_000,
+                actual_lamport_reserves,
+                is_completed: false,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        data.resize(LegacyBondingCurve::LEN, 0);
+
+        let new_data = BondingCurve::migrate_data(&data).unwrap();
+        assert_eq!(new_data.len(), 8 + BondingCurve::INIT_SPACE);
+        let migrated = BondingCurve::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, BONDING_CURVE_VERSION);
+        assert_eq!(migrated.actual_lamport_reserves, actual_lamport_reserves);
+        assert_eq!(migrated.virtual_lamport_reserves, 72_000_000_000);
+        assert_eq!(migrated.virtual_token_reserves, 600_000_000_000_000);
+        assert!(!migrated.is_completed && !migrated.is_migrated && !migrated.is_withdrawn);
+
+        //  the curve holds its rent plus the reserves, the payer only adds the rent of the new bytes
+        let rent = Rent::default();
+        let lamports = rent.minimum_balance(data.len()) + actual_lamport_reserves;
+        let (top_up_lamports, refund_lamports) = calc_resize_rent(&rent, data.len(), new_data.len());
+        let new_lamports = lamports + top_up_lamports - refund_lamports;
+        assert_eq!(refund_lamports, 0);
+        assert_eq!(new_lamports - lamports, rent.minimum_balance(new_data.len()) - rent.minimum_balance(data.len()));
+        assert_eq!(new_lamports - rent.minimum_balance(new_data.len()), actual_lamport_reserves);
+
+        //  curves on the current layout are left alone, unknown versions are rejected
+        assert_eq!(
+            BondingCurve::migrate_data(&new_data).unwrap_err(),
+            CustomError::AccountAlreadyMigrated.into()
+        );
+        let mut future_data = new_data.clone();
+        future_data[8] = BONDING_CURVE_VERSION + 1;
+        assert_eq!(
+            BondingCurve::migrate_data(&future_data).unwrap_err(),
+            CustomError::UnsupportedLayoutVersion.into()
+        );
+    }
+}
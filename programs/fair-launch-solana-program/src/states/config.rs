@@ -1,10 +1,601 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
+use crate::constants::{ADMIN_ADDRESS, BPS_DENOMINATOR, CONFIG_VERSION, DEFAULT_VIRTUAL_LAMPORT_RESERVES, LEGACY_LAYOUT_VERSION, MAX_FEE_SHARES, MAX_FEE_TIERS, MAX_VESTING_DURATION_SECONDS};
+use crate::errors::CustomError;
+use crate::states::VestingParams;
+use crate::utils::{mul_div_floor, percent_to_bps, stored_layout_version, to_u64};
 
+//  part of every curve trading fee paid to `recipient`, the fee recipient keeps whatever the shares leave
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeShare {
+    pub recipient: Pubkey,
+    pub weight_bps: u16,
+}
+
+//  discounted fees for traders whose cumulative volume reached `min_volume_lamports`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeTier {
+    pub min_volume_lamports: u64,
+    pub buy_fee_bps: u16,
+    pub sell_fee_bps: u16,
+}
+
+//  completion threshold, supply and initial virtual reserves of new curves
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CurveParams {
+    pub lamports_needed_to_complete_curve: u64,
+    pub total_token_supply: u64,
+    pub initial_virtual_lamport_reserves: u64,
+    pub initial_virtual_token_reserves: u64,
+    pub min_virtual_lamport_reserves: u64,
+    pub max_virtual_lamport_reserves: u64,
+    pub min_virtual_token_reserves: u64,
+    pub max_virtual_token_reserves: u64,
+    pub max_reserved_bps: u16, //  largest share of the supply a launch can reserve for vesting, 0 disables reservations
+    pub min_vesting_duration_seconds: i64,
+}
+
+impl FeeShare {
+    //  lamports owed to each share out of `fee_lamports`, rounded down so the fee recipient keeps the dust
+    pub fn calc_split(fee_shares: &[FeeShare], fee_lamports: u64) -> Result<Vec<u64>> {
+        fee_shares
+            .iter()
+            .map(|fee_share| {
+                to_u64(mul_div_floor(
+                    fee_lamports as u128,
+                    fee_share.weight_bps as u128,
+                    BPS_DENOMINATOR as u128,
+                )?)
+            })
+            .collect()
+    }
+}
+
+//  new fields take their bytes from `reserved` and must be valid when zeroed,
+//  the account size never changes, `migrate_config` only upgrades legacy accounts
 #[account]
+#[derive(InitSpace)]
 pub struct Config {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey, //  proposed authority, default pubkey when no transfer is pending
+    pub fee_recipient: Pubkey,
+    pub withdraw_destination: Pubkey,
+    pub lamports_needed_to_complete_curve: u64,
+    pub total_token_supply: u64,
+    pub buy_fee_bps: u16,
+    pub sell_fee_bps: u16,
+    pub launch_fee_lamports: u64,
+    pub admin_only_launch: bool,
+    pub initial_virtual_lamport_reserves: u64,
+    pub initial_virtual_token_reserves: u64,
+    //  bounds for per-launch overrides of the initial virtual reserves
+    pub min_virtual_lamport_reserves: u64,
+    pub max_virtual_lamport_reserves: u64,
+    pub min_virtual_token_reserves: u64,
+    pub max_virtual_token_reserves: u64,
+    pub paused: bool, //  halts launches and trading on every curve
+    pub fee_shares: [FeeShare; MAX_FEE_SHARES], //  only the first `fee_share_count` entries are used
+    pub fee_share_count: u8,
+    pub creator_fee_bps: u16, //  charged on top of the buy and sell fees, accrued for the curve creator
+    pub referral_fee_bps: u16, //  share of the protocol fee accrued for the referrer of a trade
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS], //  only the first `fee_tier_count` entries are used, by increasing volume
+    pub fee_tier_count: u8,
+    //  limits on the reserved allocation of new launches, launches are permissionless so the creator picks the beneficiary
+    pub max_reserved_bps: u16,
+    pub min_vesting_duration_seconds: i64,
+    pub reserved: [u8; 56],
+}
+
+impl Config {
+    //  legacy accounts become admin-only launches under the deploy-time admin
+    pub fn from_legacy(legacy_config: LegacyConfig) -> Result<Config> {
+        Ok(Config {
+            version: CONFIG_VERSION,
+            authority: ADMIN_ADDRESS,
+            pending_authority: Pubkey::default(),
+            fee_recipient: legacy_config.fee_recipient,
+            withdraw_destination: legacy_config.fee_recipient,
+            lamports_needed_to_complete_curve: legacy_config.lamports_needed_to_complete_curve,
+            total_token_supply: legacy_config.total_token_supply,
+            buy_fee_bps: percent_to_bps(legacy_config.buy_fee_percent)?,
+            sell_fee_bps: percent_to_bps(legacy_config.sell_fee_percent)?,
+            launch_fee_lamports: 0,
+            admin_only_launch: true,
+            initial_virtual_lamport_reserves: DEFAULT_VIRTUAL_LAMPORT_RESERVES,
+            initial_virtual_token_reserves: legacy_config.total_token_supply,
+            min_virtual_lamport_reserves: DEFAULT_VIRTUAL_LAMPORT_RESERVES,
+            max_virtual_lamport_reserves: DEFAULT_VIRTUAL_LAMPORT_RESERVES,
+            min_virtual_token_reserves: legacy_config.total_token_supply,
+            max_virtual_token_reserves: legacy_config.total_token_supply,
+            paused: false,
+            fee_shares: [FeeShare::default(); MAX_FEE_SHARES],
+            fee_share_count: 0,
+            creator_fee_bps: 0,
+            referral_fee_bps: 0,
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            fee_tier_count: 0,
+            max_reserved_bps: 0,
+            min_vesting_duration_seconds: 0,
+            reserved: [0; 56],
+        })
+    }
+
+    //  data of a `global-config` account rewritten to the current layout, picked by its stored version
+    pub fn migrate_data(data: &[u8]) -> Result<Vec<u8>> {
+        let config = match stored_layout_version(data, &Config::DISCRIMINATOR, LegacyConfig::LEN)? {
+            LEGACY_LAYOUT_VERSION => Config::from_legacy(LegacyConfig::deserialize(&mut &data[8..])?)?,
+            CONFIG_VERSION => return err!(CustomError::AccountAlreadyMigrated),
+            _ => return err!(CustomError::UnsupportedLayoutVersion),
+        };
+        //  legacy configs were never range checked
+        config.validate()?;
+
+        let mut new_data = Vec::with_capacity(8 + Config::INIT_SPACE);
+        config.try_serialize(&mut new_data)?;
+
+        Ok(new_data)
+    }
+
+    pub fn curve_params(&self) -> CurveParams {
+        CurveParams {
+            lamports_needed_to_complete_curve: self.lamports_needed_to_complete_curve,
+            total_token_supply: self.total_token_supply,
+            initial_virtual_lamport_reserves: self.initial_virtual_lamport_reserves,
+            initial_virtual_token_reserves: self.initial_virtual_token_reserves,
+            min_virtual_lamport_reserves: self.min_virtual_lamport_reserves,
+            max_virtual_lamport_reserves: self.max_virtual_lamport_reserves,
+            min_virtual_token_reserves: self.min_virtual_token_reserves,
+            max_virtual_token_reserves: self.max_virtual_token_reserves,
+            max_reserved_bps: self.max_reserved_bps,
+            min_vesting_duration_seconds: self.min_vesting_duration_seconds,
+        }
+    }
+
+    pub fn set_curve_params(&mut self, params: CurveParams) {
+        self.lamports_needed_to_complete_curve = params.lamports_needed_to_complete_curve;
+        self.total_token_supply = params.total_token_supply;
+        self.initial_virtual_lamport_reserves = params.initial_virtual_lamport_reserves;
+        self.initial_virtual_token_reserves = params.initial_virtual_token_reserves;
+        self.min_virtual_lamport_reserves = params.min_virtual_lamport_reserves;
+        self.max_virtual_lamport_reserves = params.max_virtual_lamport_reserves;
+        self.min_virtual_token_reserves = params.min_virtual_token_reserves;
+        self.max_virtual_token_reserves = params.max_virtual_token_reserves;
+        self.max_reserved_bps = params.max_reserved_bps;
+        self.min_vesting_duration_seconds = params.min_vesting_duration_seconds;
+    }
+
+    pub fn active_fee_shares(&self) -> &[FeeShare] {
+        &self.fee_shares[..self.fee_share_count as usize]
+    }
+
+    pub fn set_fee_shares(&mut self, fee_shares: Vec<FeeShare>) -> Result<()> {
+        require!(
+            fee_shares.len() <= MAX_FEE_SHARES,
+            CustomError::IncorrectValue
+        );
+
+        self.fee_shares = [FeeShare::default(); MAX_FEE_SHARES];
+        self.fee_shares[..fee_shares.len()].copy_from_slice(&fee_shares);
+        self.fee_share_count = fee_shares.len() as u8;
+
+        Ok(())
+    }
+
+    pub fn active_fee_tiers(&self) -> &[FeeTier] {
+        &self.fee_tiers[..self.fee_tier_count as usize]
+    }
+
+    pub fn set_fee_tiers(&mut self, fee_tiers: Vec<FeeTier>) -> Result<()> {
+        require!(
+            fee_tiers.len() <= MAX_FEE_TIERS,
+            CustomError::IncorrectValue
+        );
+
+        self.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        self.fee_tiers[..fee_tiers.len()].copy_from_slice(&fee_tiers);
+        self.fee_tier_count = fee_tiers.len() as u8;
+
+        Ok(())
+    }
+
+    //  fee of the highest tier reached by `volume_lamports`, the base fee below the first tier
+    pub fn trader_fee_bps(&self, is_buy: bool, volume_lamports: u64) -> u16 {
+        let fee_tier = self
+            .active_fee_tiers()
+            .iter()
+            .rev()
+            .find(|fee_tier| volume_lamports >= fee_tier.min_volume_lamports);
+
+        match (fee_tier, is_buy) {
+            (Some(fee_tier), true) => fee_tier.buy_fee_bps,
+            (Some(fee_tier), false) => fee_tier.sell_fee_bps,
+            (None, true) => self.buy_fee_bps,
+            (None, false) => self.sell_fee_bps,
+        }
+    }
+
+    //  fee share recipients are passed as remaining accounts, in config order
+    pub fn validate_fee_share_accounts(&self, accounts: &[AccountInfo]) -> Result<()> {
+        let fee_shares = self.active_fee_shares();
+        require!(
+            accounts.len() == fee_shares.len(),
+            CustomError::IncorrectFeeRecipient
+        );
+        for (account, fee_share) in accounts.iter().zip(fee_shares) {
+            require!(
+                account.key() == fee_share.recipient && account.is_writable,
+                CustomError::IncorrectFeeRecipient
+            );
+        }
+
+        Ok(())
+    }
+
+    //  fee share recipients are passed to `update_fee_shares` as remaining accounts, in config order,
+    //  a share below the rent exempt minimum paid to an unfunded recipient would fail every trade
+    pub fn validate_fee_share_recipients(&self, accounts: &[AccountInfo], rent: &Rent) -> Result<()> {
+        let fee_shares = self.active_fee_shares();
+        require!(
+            accounts.len() == fee_shares.len(),
+            CustomError::IncorrectFeeRecipient
+        );
+        for (account, fee_share) in accounts.iter().zip(fee_shares) {
+            require!(
+                account.key() == fee_share.recipient,
+                CustomError::IncorrectFeeRecipient
+            );
+            require!(
+                rent.is_exempt(account.lamports(), account.data_len()),
+                CustomError::FeeRecipientNotRentExempt
+            );
+        }
+
+        Ok(())
+    }
+
+    //  range checks run after initialization and after every update
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.fee_recipient != Pubkey::default() && self.withdraw_destination != Pubkey::default(),
+            CustomError::IncorrectValue
+        );
+        //  a 100% fee would leave nothing to trade
+        require!(
+            (self.buy_fee_bps as u64 + self.creator_fee_bps as u64) < BPS_DENOMINATOR
+                && (self.sell_fee_bps as u64 + self.creator_fee_bps as u64) < BPS_DENOMINATOR,
+            CustomError::IncorrectValue
+        );
+        require!(
+            self.lamports_needed_to_complete_curve > 0 && self.total_token_supply > 0,
+            CustomError::IncorrectValue
+        );
+
+        require!(
+            self.referral_fee_bps as u64 <= BPS_DENOMINATOR,
+            CustomError::IncorrectValue
+        );
+
+        //  tiers only ever discount the base fees and are ordered by volume
+        require!(
+            self.fee_tier_count as usize <= MAX_FEE_TIERS,
+            CustomError::IncorrectValue
+        );
+        let mut previous_min_volume_lamports = 0;
+        for fee_tier in self.active_fee_tiers() {
+            require!(
+                fee_tier.min_volume_lamports > previous_min_volume_lamports
+                    && fee_tier.buy_fee_bps <= self.buy_fee_bps
+                    && fee_tier.sell_fee_bps <= self.sell_fee_bps,
+                CustomError::IncorrectValue
+            );
+            previous_min_volume_lamports = fee_tier.min_volume_lamports;
+        }
+
+        //  the shares can never hand out more than the whole fee
+        require!(
+            self.fee_share_count as usize <= MAX_FEE_SHARES,
+            CustomError::IncorrectValue
+        );
+        let mut total_weight_bps: u64 = 0;
+        for fee_share in self.active_fee_shares() {
+            require!(
+                fee_share.recipient != Pubkey::default() && fee_share.weight_bps > 0,
+                CustomError::IncorrectValue
+            );
+            total_weight_bps += fee_share.weight_bps as u64;
+        }
+        require!(
+            total_weight_bps <= BPS_DENOMINATOR,
+            CustomError::IncorrectValue
+        );
+
+        //  the default virtual reserves must sit inside the override bounds
+        require!(
+            self.min_virtual_lamport_reserves > 0
+                && self.min_virtual_lamport_reserves <= self.initial_virtual_lamport_reserves
+                && self.initial_virtual_lamport_reserves <= self.max_virtual_lamport_reserves,
+            CustomError::IncorrectValue
+        );
+        //  the curve can never owe more tokens than were minted to it
+        require!(
+            self.min_virtual_token_reserves > 0
+                && self.min_virtual_token_reserves <= self.initial_virtual_token_reserves
+                && self.initial_virtual_token_reserves <= self.max_virtual_token_reserves
+                && self.max_virtual_token_reserves <= self.total_token_supply,
+            CustomError::IncorrectValue
+        );
+
+        //  the curve always keeps part of the supply, and reserved allocations can be forced to vest
+        require!(
+            (self.max_reserved_bps as u64) < BPS_DENOMINATOR
+                && (0..=MAX_VESTING_DURATION_SECONDS).contains(&self.min_vesting_duration_seconds),
+            CustomError::IncorrectValue
+        );
+
+        Ok(())
+    }
+
+    //  a creator can only reserve up to the configured share of the supply, vested over at least the configured duration
+    pub fn validate_vesting_params(&self, vesting_params: &VestingParams) -> Result<()> {
+        require!(
+            vesting_params.reserved_bps <= self.max_reserved_bps
+                && vesting_params.duration_seconds >= self.min_vesting_duration_seconds,
+            CustomError::IncorrectValue
+        );
+
+        Ok(())
+    }
+
+    //  initial virtual reserves of a new curve, as (virtual lamports, virtual tokens)
+    pub fn resolve_virtual_reserves(
+        &self,
+        virtual_lamport_reserves: Option<u64>,
+        virtual_token_reserves: Option<u64>,
+    ) -> Result<(u64, u64)> {
+        let virtual_lamport_reserves = match virtual_lamport_reserves {
+            Some(reserves) => {
+                require!(
+                    (self.min_virtual_lamport_reserves..=self.max_virtual_lamport_reserves).contains(&reserves),
+                    CustomError::IncorrectValue
+                );
+                reserves
+            }
+            None => self.initial_virtual_lamport_reserves,
+        };
+        let virtual_token_reserves = match virtual_token_reserves {
+            Some(reserves) => {
+                require!(
+                    (self.min_virtual_token_reserves..=self.max_virtual_token_reserves).contains(&reserves),
+                    CustomError::IncorrectValue
+                );
+                reserves
+            }
+            None => self.initial_virtual_token_reserves,
+        };
+
+        Ok((virtual_lamport_reserves, virtual_token_reserves))
+    }
+}
+
+//  layout of `global-config` accounts created before fees moved to basis points
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyConfig {
     pub fee_recipient: Pubkey,
     pub lamports_needed_to_complete_curve: u64,
     pub total_token_supply: u64,
     pub buy_fee_percent: f64,
     pub sell_fee_percent: f64,
 }
+
+impl LegacyConfig {
+    pub const LEN: usize = 8 + std::mem::size_of::<LegacyConfig>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account_data<T: AnchorSerialize>(fields: &T) -> Vec<u8> {
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        data.extend(fields.try_to_vec().unwrap());
+        data
+    }
+
+    #[test]
+    fn legacy_config_is_rewritten_to_the_current_layout() {
+        let fee_recipient = Pubkey::new_unique();
+        let data = account_data(&LegacyConfig {
+            fee_recipient,
+            lamports_needed_to_complete_curve: 85_000_000_000,
+            total_token_supply: 1_000_000_000_000_000,
+            buy_fee_percent: 1.0,
+            sell_fee_percent: 1.5,
+        });
+        assert_eq!(data.len(), LegacyConfig::LEN);
+
+        let new_data = Config::migrate_data(&data).unwrap();
+        assert_eq!(new_data.len(), 8 + Config::INIT_SPACE);
+        let config = Config::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.authority, ADMIN_ADDRESS);
+        assert_eq!(config.fee_recipient, fee_recipient);
+        assert_eq!(config.withdraw_destination, fee_recipient);
+        assert_eq!(config.lamports_needed_to_complete_curve, 85_000_000_000);
+        assert_eq!((config.buy_fee_bps, config.sell_fee_bps), (100, 150));
+        assert!(config.admin_only_launch);
+        assert_eq!(config.initial_virtual_token_reserves, 1_000_000_000_000_000);
+        config.validate().unwrap();
+
+        //  accounts on the current layout are left alone, unknown versions are rejected
+        assert_eq!(
+            Config::migrate_data(&new_data).unwrap_err(),
+            CustomError::AccountAlreadyMigrated.into()
+        );
+        let mut future_data = new_data.clone();
+        future_data[8] = CONFIG_VERSION + 1;
+        assert_eq!(
+            Config::migrate_data(&future_data).unwrap_err(),
+            CustomError::UnsupportedLayoutVersion.into()
+        );
+    }
+
+    #[test]
+    fn legacy_config_out_of_range_is_not_migrated() {
+        let data = account_data(&LegacyConfig {
+            fee_recipient: Pubkey::new_unique(),
+            lamports_needed_to_complete_curve: 85_000_000_000,
+            total_token_supply: 1_000_000_000_000_000,
+            buy_fee_percent: 100.0,
+            sell_fee_percent: 1.0,
+        });
+
+        assert_eq!(
+            Config::migrate_data(&data).unwrap_err(),
+            CustomError::IncorrectValue.into()
+        );
+    }
+
+
+    fn config_with_fee_shares(fee_shares: &[FeeShare]) -> Config {
+        let mut config = Config::from_legacy(LegacyConfig {
+            fee_recipient: Pubkey::new_unique(),
+            lamports_needed_to_complete_curve: 85_000_000_000,
+            total_token_supply: 1_000_000_000_000_000,
+            buy_fee_percent: 1.0,
+            sell_fee_percent: 1.0,
+        })
+        .unwrap();
+        config.set_fee_shares(fee_shares.to_vec()).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    fn account_info<'a>(key: &'a Pubkey, is_writable: bool, lamports: &'a mut u64) -> AccountInfo<'a> {
+        AccountInfo::new(key, false, is_writable, lamports, &mut [], &anchor_lang::system_program::ID, false, 0)
+    }
+
+    #[test]
+    fn fee_is_split_by_weight() {
+        let fee_shares = [
+            FeeShare { recipient: Pubkey::new_unique(), weight_bps: 5_000 },
+            FeeShare { recipient: Pubkey::new_unique(), weight_bps: 2_500 },
+            FeeShare { recipient: Pubkey::new_unique(), weight_bps: 1_000 },
+        ];
+
+        assert_eq!(
+            FeeShare::calc_split(&fee_shares, 1_000_000).unwrap(),
+            vec![500_000, 250_000, 100_000]
+        );
+        assert_eq!(FeeShare::calc_split(&[], 1_000_000).unwrap(), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn fee_share_dust_is_left_to_the_fee_recipient() {
+        let fee_shares = [
+            FeeShare { recipient: Pubkey::new_unique(), weight_bps: 3_333 },
+            FeeShare { recipient: Pubkey::new_unique(), weight_bps: 3_333 },
+            FeeShare { recipient: Pubkey::new_unique(), weight_bps: 3_334 },
+        ];
+
+        //  every share rounds down, the fee recipient keeps what they leave even at full weight
+        assert_eq!(FeeShare::calc_split(&fee_shares, 100).unwrap(), vec![33, 33, 33]);
+        assert_eq!(FeeShare::calc_split(&fee_shares, 2).unwrap(), vec![0, 0, 0]);
+        for fee_lamports in [0, 1, 7, 9_999, 10_001, u64::MAX] {
+            let split = FeeShare::calc_split(&fee_shares, fee_lamports).unwrap();
+            assert!(split.iter().map(|&share| share as u128).sum::<u128>() <= fee_lamports as u128);
+        }
+    }
+
+    #[test]
+    fn fee_share_accounts_must_follow_the_config() {
+        let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let config = config_with_fee_shares(&[
+            FeeShare { recipient: recipients[0], weight_bps: 1_000 },
+            FeeShare { recipient: recipients[1], weight_bps: 500 },
+        ]);
+        let other = Pubkey::new_unique();
+        let (mut lamports_0, mut lamports_1, mut lamports_2) = (0, 0, 0);
+        let first = account_info(&recipients[0], true, &mut lamports_0);
+        let second = account_info(&recipients[1], true, &mut lamports_1);
+        let stranger = account_info(&other, true, &mut lamports_2);
+        let mut read_only = second.clone();
+        read_only.is_writable = false;
+
+        config.validate_fee_share_accounts(&[first.clone(), second.clone()]).unwrap();
+
+        for accounts in [
+            vec![],
+            vec![first.clone()],
+            vec![second.clone(), first.clone()],
+            vec![first.clone(), stranger.clone()],
+            vec![first.clone(), read_only.clone()],
+            vec![first.clone(), second.clone(), stranger.clone()],
+        ] {
+            assert_eq!(
+                config.validate_fee_share_accounts(&accounts).unwrap_err(),
+                CustomError::IncorrectFeeRecipient.into()
+            );
+        }
+
+        //  no fee shares configured, no accounts expected
+        let config = config_with_fee_shares(&[]);
+        config.validate_fee_share_accounts(&[]).unwrap();
+        assert!(config.validate_fee_share_accounts(&[first]).is_err());
+    }
+
+    #[test]
+    fn fee_share_recipients_must_be_rent_exempt() {
+        let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let config = config_with_fee_shares(&[
+            FeeShare { recipient: recipients[0], weight_bps: 1_000 },
+            FeeShare { recipient: recipients[1], weight_bps: 500 },
+        ]);
+        let rent = Rent::default();
+        let (mut lamports_0, mut lamports_1, mut lamports_2) =
+            (rent.minimum_balance(0), rent.minimum_balance(0), rent.minimum_balance(0) - 1);
+        let first = account_info(&recipients[0], false, &mut lamports_0);
+        let second = account_info(&recipients[1], false, &mut lamports_1);
+        let unfunded = account_info(&recipients[1], false, &mut lamports_2);
+
+        config.validate_fee_share_recipients(&[first.clone(), second.clone()], &rent).unwrap();
+        assert_eq!(
+            config.validate_fee_share_recipients(&[first.clone(), unfunded], &rent).unwrap_err(),
+            CustomError::FeeRecipientNotRentExempt.into()
+        );
+        for accounts in [vec![], vec![first.clone()], vec![second.clone(), first.clone()]] {
+            assert_eq!(
+                config.validate_fee_share_recipients(&accounts, &rent).unwrap_err(),
+                CustomError::IncorrectFeeRecipient.into()
+            );
+        }
+    }
+
+    #[test]
+    fn reserved_allocations_are_capped_by_the_config() {
+        let mut config = config_with_fee_shares(&[]);
+        let vesting_params = |reserved_bps: u16, duration_seconds: i64| VestingParams {
+            beneficiary: Pubkey::new_unique(),
+            reserved_bps,
+            cliff_seconds: 0,
+            duration_seconds,
+        };
+
+        //  legacy configs allow no reservation at all
+        assert!(config.validate_vesting_params(&vesting_params(1, 100)).is_err());
+
+        config.max_reserved_bps = 1_000;
+        config.min_vesting_duration_seconds = 3_600;
+        config.validate().unwrap();
+        config.validate_vesting_params(&vesting_params(1_000, 3_600)).unwrap();
+        assert!(config.validate_vesting_params(&vesting_params(1_001, 3_600)).is_err());
+        assert!(config.validate_vesting_params(&vesting_params(1_000, 3_599)).is_err());
+
+        config.max_reserved_bps = 10_000;
+        assert!(config.validate().is_err());
+        config.max_reserved_bps = 1_000;
+        config.min_vesting_duration_seconds = -1;
+        assert!(config.validate().is_err());
+        config.min_vesting_duration_seconds = MAX_VESTING_DURATION_SECONDS + 1;
+        assert!(config.validate().is_err());
+    }
+}
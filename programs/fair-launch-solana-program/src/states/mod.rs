@@ -1,4 +1,14 @@
 pub mod config;
 pub use config::*;
 pub mod bonding_curve;
-pub use bonding_curve::*;
\ No newline at end of file
+pub use bonding_curve::*;
+pub mod pool;
+pub use pool::*;
+pub mod vesting;
+pub use vesting::*;
+pub mod referrer;
+pub use referrer::*;
+pub mod trader_stats;
+pub use trader_stats::*;
+pub mod price_oracle;
+pub use price_oracle::*;
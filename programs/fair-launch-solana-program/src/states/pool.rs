@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+use crate::constants::{POOL_FEE_BPS, POOL_SEED_IN_BYTES, PRICE_PRECISION};
+use crate::errors::CustomError;
+use crate::states::BondingCurve;
+use crate::utils::{fee_ceil, mul_div_ceil, mul_div_floor, sqrt_floor, to_u64};
+
+//  constant product pool a completed bonding curve graduates into
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub token_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_reserves: u64,
+    pub lamport_reserves: u64,
+    pub lp_supply: u64,
+    pub bump: u8,
+}
+
+impl Pool {
+    pub fn get_signer<'a>(mint: &'a Pubkey, bump: &'a u8) -> [&'a [u8]; 3] {
+        [
+            POOL_SEED_IN_BYTES,
+            mint.as_ref(),
+            std::slice::from_ref(bump),
+        ]
+    }
+
+    //  liquidity a completed curve seeds the pool with, the curve lamports are matched with tokens at the
+    //  final curve price so the pool opens where the curve stopped, the rest of the curve tokens is burned
+    pub fn calc_seed_reserves(bonding_curve: &BondingCurve, curve_token_balance: u64) -> Result<(u64, u64)> {
+        let lamport_reserves = bonding_curve.actual_lamport_reserves;
+        let token_reserves = to_u64(mul_div_floor(
+            lamport_reserves as u128,
+            bonding_curve.virtual_token_reserves as u128,
+            bonding_curve.virtual_lamport_reserves as u128,
+        )?)?;
+
+        Ok((lamport_reserves, token_reserves.min(curve_token_balance)))
+    }
+
+    //  LP supply minted against the seed liquidity, it is never minted to anyone so stays locked
+    pub fn calc_initial_lp_supply(lamport_reserves: u64, token_reserves: u64) -> Result<u64> {
+        to_u64(sqrt_floor(lamport_reserves as u128 * token_reserves as u128))
+    }
+
+    //  amount out for an exact-in swap, the pool fee stays in the reserves
+    pub fn calc_amount_out(&self, amount_in: u64, is_buy: bool) -> Result<u64> {
+        let (reserve_in, reserve_out) = if is_buy {
+            (self.lamport_reserves, self.token_reserves)
+        } else {
+            (self.token_reserves, self.lamport_reserves)
+        };
+
+        let amount_in_after_fee = amount_in
+            .checked_sub(fee_ceil(amount_in, POOL_FEE_BPS)?)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)? as u128;
+        let new_reserve_in = (reserve_in as u128)
+            .checked_add(amount_in_after_fee)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+        to_u64(mul_div_floor(reserve_out as u128, amount_in_after_fee, new_reserve_in)?)
+    }
+
+    //  spot price in lamports per token unit, scaled by `PRICE_PRECISION` like the curve price
+    pub fn calc_spot_price(&self) -> Result<u64> {
+        require!(self.token_reserves > 0, CustomError::PoolEmpty);
+
+        to_u64(mul_div_floor(
+            self.lamport_reserves as u128,
+            PRICE_PRECISION,
+            self.token_reserves as u128,
+        )?)
+    }
+
+    //  lamports and tokens to deposit for `lp_amount`, rounded up in favour of the pool
+    pub fn calc_deposit(&self, lp_amount: u64) -> Result<(u64, u64)> {
+        let lamports = to_u64(mul_div_ceil(self.lamport_reserves as u128, lp_amount as u128, self.lp_supply as u128)?)?;
+        let tokens = to_u64(mul_div_ceil(self.token_reserves as u128, lp_amount as u128, self.lp_supply as u128)?)?;
+
+        Ok((lamports, tokens))
+    }
+
+    //  lamports and tokens returned for burning `lp_amount`, rounded down in favour of the pool
+    pub fn calc_withdraw(&self, lp_amount: u64) -> Result<(u64, u64)> {
+        let lamports = to_u64(mul_div_floor(self.lamport_reserves as u128, lp_amount as u128, self.lp_supply as u128)?)?;
+        let tokens = to_u64(mul_div_floor(self.token_reserves as u128, lp_amount as u128, self.lp_supply as u128)?)?;
+
+        Ok((lamports, tokens))
+    }
+
+    pub fn update_reserves(&mut self, lamport_reserves: u64, token_reserves: u64, lp_supply: u64) {
+        self.lamport_reserves = lamport_reserves;
+        self.token_reserves = token_reserves;
+        self.lp_supply = lp_supply;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::PriceOracle;
+
+    fn pool(lamport_reserves: u64, token_reserves: u64) -> Pool {
+        Pool {
+            token_mint: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            token_reserves,
+            lamport_reserves,
+            lp_supply: Pool::calc_initial_lp_supply(lamport_reserves, token_reserves).unwrap(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn initial_lp_supply_is_the_geometric_mean() {
+        assert_eq!(Pool::calc_initial_lp_supply(1_000_000_000, 4_000_000_000).unwrap(), 2_000_000_000);
+        assert_eq!(Pool::calc_initial_lp_supply(2, 3).unwrap(), 2);
+        assert_eq!(Pool::calc_initial_lp_supply(0, 3).unwrap(), 0);
+    }
+
+    #[test]
+    fn pool_opens_at_the_final_curve_price() {
+        //  curves launched with 100 SOL and the whole supply as virtual reserves, completed after 85 and 100 SOL
+        for (actual_lamport_reserves, virtual_token_reserves) in [
+            (85_000_000_000u64, 540_540_540_540_541u64),
+            (100_000_000_000, 500_000_000_000_000),
+        ] {
+            let mut curve = BondingCurve::deserialize(&mut &[0u8; 512][..]).unwrap();
+            curve.virtual_lamport_reserves = 100_000_000_000 + actual_lamport_reserves;
+            curve.virtual_token_reserves = virtual_token_reserves;
+            curve.actual_lamport_reserves = actual_lamport_reserves;
+            curve.is_completed = true;
+
+            //  the curve still holds every unsold token
+            let (lamport_reserves, token_reserves) = Pool::calc_seed_reserves(&curve, virtual_token_reserves).unwrap();
+            assert_eq!(lamport_reserves, actual_lamport_reserves);
+            assert!(token_reserves < virtual_token_reserves);
+            assert_eq!(
+                pool(lamport_reserves, token_reserves).calc_spot_price().unwrap(),
+                PriceOracle::calc_spot_price(&curve, None).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn liquidity_rounds_in_favour_of_the_pool() {
+        let pool = pool(1_000_000_001, 3_000_000_007);
+
+        let (lamports_in, tokens_in) = pool.calc_deposit(1_000).unwrap();
+        let (lamports_out, tokens_out) = pool.calc_withdraw(1_000).unwrap();
+        assert_eq!(lamports_in, lamports_out + 1);
+        assert_eq!(tokens_in, tokens_out + 1);
+
+        //  withdrawing the whole supply returns the whole reserves
+        assert_eq!(pool.calc_withdraw(pool.lp_supply).unwrap(), (pool.lamport_reserves, pool.token_reserves));
+    }
+
+    #[test]
+    fn swap_fee_stays_in_the_pool() {
+        let pool = pool(1_000_000_000, 1_000_000_000);
+
+        //  997 of 1000 lamports are swapped after the 30 bps fee
+        assert_eq!(pool.calc_amount_out(1_000, true).unwrap(), 996);
+        assert_eq!(pool.calc_amount_out(0, true).unwrap(), 0);
+    }
+}
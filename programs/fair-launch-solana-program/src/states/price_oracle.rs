@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+use crate::constants::{MAX_PRICE_OBSERVATIONS, PRICE_OBSERVATION_INTERVAL_SECONDS};
+use crate::errors::CustomError;
+use crate::states::{BondingCurve, Pool};
+use crate::utils::to_u64;
+
+//  cumulative spot price of a token at `timestamp`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PriceObservation {
+    pub timestamp: i64,
+    pub price_cumulative: u128,
+}
+
+//  cumulative spot price of a launched token, with a ring buffer of past values written at most once per observation interval,
+//  the curve feeds it until it migrates and the pool it graduated into afterwards
+#[account]
+#[derive(InitSpace)]
+pub struct PriceOracle {
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub price_cumulative: u128, //  spot price integrated over seconds, wraps around on overflow
+    pub price_updated_at: i64, //  unix timestamp `price_cumulative` was last brought up to, 0 before the first update
+    pub observation_index: u8, //  slot of the newest observation
+    pub observation_count: u8,
+    pub observations: [PriceObservation; MAX_PRICE_OBSERVATIONS],
+}
+
+impl PriceOracle {
+    //  spot price the token currently trades at, withdrawn curves left the program and have no price anymore
+    pub fn calc_spot_price(bonding_curve: &BondingCurve, pool: Option<&Pool>) -> Result<u64> {
+        require!(!bonding_curve.is_withdrawn, CustomError::CurveNotTrading);
+
+        match (bonding_curve.is_migrated, pool) {
+            (false, _) => BondingCurve::calc_spot_price(
+                bonding_curve.virtual_lamport_reserves,
+                bonding_curve.virtual_token_reserves,
+            ),
+            (true, Some(pool)) => pool.calc_spot_price(),
+            (true, None) => err!(CustomError::PoolRequired),
+        }
+    }
+
+    //  `price_cumulative` brought up to `now`, `spot_price` held since the last update
+    pub fn calc_price_cumulative(&self, spot_price: u64, now: i64) -> u128 {
+        if self.price_updated_at == 0 || now <= self.price_updated_at {
+            return self.price_cumulative;
+        }
+
+        let elapsed = (now - self.price_updated_at) as u128;
+
+        self.price_cumulative.wrapping_add(spot_price as u128 * elapsed)
+    }
+
+    //  must run with the price held so far before every change of the reserves the price is read from
+    pub fn update_price_cumulative(&mut self, spot_price: u64, now: i64) {
+        self.price_cumulative = self.calc_price_cumulative(spot_price, now);
+        self.price_updated_at = now;
+    }
+
+    //  snapshot of `price_cumulative` taken at `now`
+    pub fn record(&mut self, now: i64) {
+        let price_cumulative = self.price_cumulative;
+
+        if self.observation_count > 0
+            && now - self.observations[self.observation_index as usize].timestamp < PRICE_OBSERVATION_INTERVAL_SECONDS
+        {
+            return;
+        }
+
+        if self.observation_count > 0 {
+            self.observation_index = ((self.observation_index as usize + 1) % MAX_PRICE_OBSERVATIONS) as u8;
+        }
+        self.observations[self.observation_index as usize] = PriceObservation { timestamp: now, price_cumulative };
+        self.observation_count = self.observation_count.saturating_add(1).min(MAX_PRICE_OBSERVATIONS as u8);
+    }
+
+    //  newest observation taken at or before `timestamp`
+    fn observation_at_or_before(&self, timestamp: i64) -> Option<&PriceObservation> {
+        (0..self.observation_count as usize)
+            .map(|age| {
+                &self.observations[(self.observation_index as usize + MAX_PRICE_OBSERVATIONS - age) % MAX_PRICE_OBSERVATIONS]
+            })
+            .find(|observation| observation.timestamp <= timestamp)
+    }
+
+    //  time weighted spot price over at least the last `window_seconds`, scaled by `PRICE_PRECISION`,
+    //  the window starts at the newest observation at or before `now - window_seconds`
+    pub fn calc_twap(&self, spot_price: u64, now: i64, window_seconds: i64) -> Result<u64> {
+        require!(window_seconds > 0, CustomError::IncorrectValue);
+        let window_start = now
+            .checked_sub(window_seconds)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+        //  nothing traded inside the window, the price stayed flat
+        if self.price_updated_at <= window_start {
+            return Ok(spot_price);
+        }
+
+        let observation = self
+            .observation_at_or_before(window_start)
+            .ok_or(CustomError::NotEnoughPriceHistory)?;
+        let price_cumulative = self.calc_price_cumulative(spot_price, now);
+
+        to_u64(
+            price_cumulative.wrapping_sub(observation.price_cumulative)
+                / (now - observation.timestamp) as u128,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::LegacyBondingCurve;
+
+    fn curve(virtual_lamport_reserves: u64) -> BondingCurve {
+        BondingCurve::from_legacy(LegacyBondingCurve {
+            virtual_token_reserves: 1_000_000_000_000_000,
+            virtual_lamport_reserves,
+            actual_lamport_reserves: 0,
+            is_completed: false,
+        })
+    }
+
+    fn price_oracle() -> PriceOracle {
+        PriceOracle {
+            mint: Pubkey::new_unique(),
+            bump: 0,
+            price_cumulative: 0,
+            price_updated_at: 0,
+            observation_index: 0,
+            observation_count: 0,
+            observations: [PriceObservation::default(); MAX_PRICE_OBSERVATIONS],
+        }
+    }
+
+    fn spot_price(bonding_curve: &BondingCurve) -> u64 {
+        PriceOracle::calc_spot_price(bonding_curve, None).unwrap()
+    }
+
+    //  brings the accumulator up to `now` and moves the curve to a new price, like a trade would
+    fn trade(price_oracle: &mut PriceOracle, bonding_curve: &mut BondingCurve, now: i64, virtual_lamport_reserves: u64) {
+        price_oracle.update_price_cumulative(spot_price(bonding_curve), now);
+        price_oracle.record(now);
+        bonding_curve.virtual_lamport_reserves = virtual_lamport_reserves;
+    }
+
+    #[test]
+    fn twap_weights_every_price_by_the_time_it_was_held() {
+        let mut price_oracle = price_oracle();
+        //  a spot price of 100_000, launched at 1_000
+        let mut bonding_curve = curve(100_000_000_000);
+        price_oracle.update_price_cumulative(spot_price(&bonding_curve), 1_000);
+        price_oracle.record(1_000);
+
+        //  doubled at 1_030, inside the first observation interval, and doubled again at 1_070
+        trade(&mut price_oracle, &mut bonding_curve, 1_030, 200_000_000_000);
+        assert_eq!(price_oracle.observation_count, 1);
+        trade(&mut price_oracle, &mut bonding_curve, 1_070, 400_000_000_000);
+        assert_eq!(price_oracle.observation_count, 2);
+        assert_eq!(price_oracle.price_cumulative, 30 * 100_000 + 40 * 200_000);
+
+        //  nothing traded since the window start, the spot price held all along
+        let spot_price = spot_price(&bonding_curve);
+        assert_eq!(price_oracle.calc_twap(spot_price, 1_130, 60).unwrap(), 400_000);
+        //  the window reaches back to the launch observation
+        assert_eq!(
+            price_oracle.calc_twap(spot_price, 1_130, 100).unwrap(),
+            (30 * 100_000 + 40 * 200_000 + 60 * 400_000) / 130
+        );
+        assert_eq!(
+            price_oracle.calc_twap(spot_price, 1_130, 200).unwrap_err(),
+            CustomError::NotEnoughPriceHistory.into()
+        );
+        assert_eq!(
+            price_oracle.calc_twap(spot_price, 1_130, 0).unwrap_err(),
+            CustomError::IncorrectValue.into()
+        );
+    }
+
+    #[test]
+    fn price_moves_from_the_curve_to_the_pool_at_graduation() {
+        let mut price_oracle = price_oracle();
+        let mut bonding_curve = curve(100_000_000_000);
+        price_oracle.update_price_cumulative(spot_price(&bonding_curve), 1_000);
+        price_oracle.record(1_000);
+
+        //  a completed curve keeps its last price until it migrates
+        bonding_curve.is_completed = true;
+        assert_eq!(spot_price(&bonding_curve), 100_000);
+        assert_eq!(price_oracle.calc_twap(spot_price(&bonding_curve), 1_100, 60).unwrap(), 100_000);
+
+        //  migrated at 1_100 into a pool priced at 300_000
+        price_oracle.update_price_cumulative(spot_price(&bonding_curve), 1_100);
+        price_oracle.record(1_100);
+        bonding_curve.is_migrated = true;
+        let pool = Pool {
+            token_mint: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            token_reserves: 1_000_000,
+            lamport_reserves: 300,
+            lp_supply: 1,
+            bump: 0,
+        };
+        assert_eq!(
+            PriceOracle::calc_spot_price(&bonding_curve, None).unwrap_err(),
+            CustomError::PoolRequired.into()
+        );
+        let pool_spot_price = PriceOracle::calc_spot_price(&bonding_curve, Some(&pool)).unwrap();
+        assert_eq!(pool_spot_price, 300_000);
+        assert_eq!(
+            price_oracle.calc_twap(pool_spot_price, 1_200, 200).unwrap(),
+            (100 * 100_000 + 100 * 300_000) / 200
+        );
+
+        //  withdrawn curves are zeroed and have no price anymore
+        let withdrawn = BondingCurve { is_migrated: false, is_withdrawn: true, ..bonding_curve.clone() };
+        assert_eq!(
+            PriceOracle::calc_spot_price(&withdrawn, None).unwrap_err(),
+            CustomError::CurveNotTrading.into()
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+
+//  registered referrer, accrues its share of the fees of referred trades until claimed
+#[account]
+#[derive(InitSpace)]
+pub struct Referrer {
+    pub wallet: Pubkey,
+    pub accrued_lamports: u64,      //  unclaimed referral fees, held by this account next to its rent
+    pub total_earned_lamports: u64, //  every referral fee ever accrued
+    pub bump: u8,
+}
+
+impl Referrer {
+    pub fn accrue(&mut self, lamports: u64) -> Result<()> {
+        self.accrued_lamports = self
+            .accrued_lamports
+            .checked_add(lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        self.total_earned_lamports = self
+            .total_earned_lamports
+            .checked_add(lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+}
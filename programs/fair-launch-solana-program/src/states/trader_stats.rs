@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+
+//  cumulative curve trading volume of a wallet, used to pick its fee tier
+#[account]
+#[derive(InitSpace)]
+pub struct TraderStats {
+    pub trader: Pubkey,
+    pub volume_lamports: u64, //  lamports paid on buys plus lamports received on sells, before fees
+    pub trade_count: u64,
+    pub referrer: Option<Pubkey>, //  wallet of the first referrer, later trades can only be referred by it
+    pub bump: u8,
+}
+
+impl TraderStats {
+    pub fn record_trade(&mut self, volume_lamports: u64) -> Result<()> {
+        self.volume_lamports = self
+            .volume_lamports
+            .checked_add(volume_lamports)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+        self.trade_count = self
+            .trade_count
+            .checked_add(1)
+            .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+
+    //  the first referrer of a trader sticks, switching would let a trader refer itself through a second wallet
+    pub fn bind_referrer(&mut self, referrer: Pubkey) -> Result<()> {
+        match self.referrer {
+            Some(bound_referrer) => require!(bound_referrer == referrer, CustomError::ReferrerMismatch),
+            None => self.referrer = Some(referrer),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn referrer_is_bound_on_first_use() {
+        let mut trader_stats = TraderStats {
+            trader: Pubkey::new_unique(),
+            volume_lamports: 0,
+            trade_count: 0,
+            referrer: None,
+            bump: 0,
+        };
+        let referrer = Pubkey::new_unique();
+
+        trader_stats.bind_referrer(referrer).unwrap();
+        assert_eq!(trader_stats.referrer, Some(referrer));
+        trader_stats.bind_referrer(referrer).unwrap();
+        assert!(trader_stats.bind_referrer(Pubkey::new_unique()).is_err());
+        assert_eq!(trader_stats.referrer, Some(referrer));
+    }
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use crate::constants::{BPS_DENOMINATOR, MAX_VESTING_DURATION_SECONDS, VESTING_SEED_IN_BYTES};
+use crate::errors::CustomError;
+use crate::utils::{mul_div_floor, to_u64};
+
+//  launch option reserving part of the supply for a beneficiary
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VestingParams {
+    pub beneficiary: Pubkey,
+    pub reserved_bps: u16,     //  share of the total supply kept out of the curve
+    pub cliff_seconds: i64,    //  nothing unlocks before the cliff
+    pub duration_seconds: i64, //  everything is unlocked at the end of the duration
+}
+
+//  escrow holding a reserved allocation, unlocked linearly from launch
+#[account]
+#[derive(InitSpace)]
+pub struct Vesting {
+    pub mint: Pubkey,
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+    pub bump: u8,
+}
+
+impl VestingParams {
+    pub fn calc_reserved_amount(&self, total_token_supply: u64) -> Result<u64> {
+        require!(
+            self.reserved_bps > 0 && (self.reserved_bps as u64) < BPS_DENOMINATOR,
+            CustomError::IncorrectValue
+        );
+        require!(
+            self.duration_seconds > 0
+                && self.duration_seconds <= MAX_VESTING_DURATION_SECONDS
+                && (0..=self.duration_seconds).contains(&self.cliff_seconds),
+            CustomError::IncorrectValue
+        );
+
+        to_u64(mul_div_floor(
+            total_token_supply as u128,
+            self.reserved_bps as u128,
+            BPS_DENOMINATOR as u128,
+        )?)
+    }
+}
+
+impl Vesting {
+    pub fn get_signer<'a>(mint: &'a Pubkey, bump: &'a u8) -> [&'a [u8]; 3] {
+        [
+            VESTING_SEED_IN_BYTES,
+            mint.as_ref(),
+            std::slice::from_ref(bump),
+        ]
+    }
+
+    //  tokens unlocked at `now`, claimed or not
+    pub fn calc_unlocked_amount(&self, now: i64) -> Result<u64> {
+        if now < self.cliff_ts {
+            return Ok(0);
+        }
+        if now >= self.end_ts {
+            return Ok(self.total_amount);
+        }
+
+        to_u64(mul_div_floor(
+            self.total_amount as u128,
+            (now - self.start_ts) as u128,
+            (self.end_ts - self.start_ts) as u128,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(reserved_bps: u16, cliff_seconds: i64, duration_seconds: i64) -> VestingParams {
+        VestingParams {
+            beneficiary: Pubkey::default(),
+            reserved_bps,
+            cliff_seconds,
+            duration_seconds,
+        }
+    }
+
+    #[test]
+    fn reserved_amount_bounds() {
+        assert_eq!(params(1_000, 0, 100).calc_reserved_amount(1_000_000).unwrap(), 100_000);
+        assert_eq!(params(9_999, 100, 100).calc_reserved_amount(u64::MAX).unwrap(), (u64::MAX as u128 * 9_999 / 10_000) as u64);
+        assert!(params(1_000, 0, MAX_VESTING_DURATION_SECONDS).calc_reserved_amount(1_000_000).is_ok());
+
+        assert!(params(0, 0, 100).calc_reserved_amount(1_000_000).is_err());
+        assert!(params(10_000, 0, 100).calc_reserved_amount(1_000_000).is_err());
+        assert!(params(1_000, 0, 0).calc_reserved_amount(1_000_000).is_err());
+        assert!(params(1_000, -1, 100).calc_reserved_amount(1_000_000).is_err());
+        assert!(params(1_000, 101, 100).calc_reserved_amount(1_000_000).is_err());
+        assert!(params(1_000, 0, MAX_VESTING_DURATION_SECONDS + 1).calc_reserved_amount(1_000_000).is_err());
+        assert!(params(1_000, 0, i64::MAX).calc_reserved_amount(1_000_000).is_err());
+    }
+
+    #[test]
+    fn unlocks_linearly_after_the_cliff() {
+        let vesting = Vesting {
+            mint: Pubkey::default(),
+            beneficiary: Pubkey::default(),
+            total_amount: 1_000,
+            claimed_amount: 0,
+            start_ts: 1_000,
+            cliff_ts: 1_250,
+            end_ts: 2_000,
+            bump: 0,
+        };
+
+        //  before the cliff
+        assert_eq!(vesting.calc_unlocked_amount(0).unwrap(), 0);
+        assert_eq!(vesting.calc_unlocked_amount(1_249).unwrap(), 0);
+        //  linear phase, measured from the start rather than the cliff
+        assert_eq!(vesting.calc_unlocked_amount(1_250).unwrap(), 250);
+        assert_eq!(vesting.calc_unlocked_amount(1_333).unwrap(), 333);
+        assert_eq!(vesting.calc_unlocked_amount(1_999).unwrap(), 999);
+        //  after the end
+        assert_eq!(vesting.calc_unlocked_amount(2_000).unwrap(), 1_000);
+        assert_eq!(vesting.calc_unlocked_amount(i64::MAX).unwrap(), 1_000);
+    }
+}
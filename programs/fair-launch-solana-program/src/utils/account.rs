@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::constants::LEGACY_LAYOUT_VERSION;
+use crate::errors::CustomError;
+use crate::utils::{sol_transfer_from_pda, sol_transfer_from_user};
+
+//  layout version stored right after the discriminator, legacy accounts have no version byte
+pub fn stored_layout_version(data: &[u8], discriminator: &[u8], legacy_len: usize) -> Result<u8> {
+    require!(
+        data.len() > 8 && data[..8] == *discriminator,
+        CustomError::IncorrectValue
+    );
+    if data.len() == legacy_len {
+        return Ok(LEGACY_LAYOUT_VERSION);
+    }
+
+    Ok(data[8])
+}
+
+//  realloc a program owned account, only rent changes hands: the payer covers the rent of added bytes
+//  and gets back the rent of removed ones, anything else the account holds stays put
+//  bytes added by the realloc are zeroed
+pub fn resize_account<'info>(
+    account: &mut AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let (top_up_lamports, refund_lamports) = calc_resize_rent(&Rent::get()?, account.data_len(), new_len);
+
+    if top_up_lamports > 0 {
+        sol_transfer_from_user(payer, account, system_program, top_up_lamports)?;
+    }
+    account.realloc(new_len, true)?;
+    if refund_lamports > 0 {
+        sol_transfer_from_pda(account, &mut payer.to_account_info(), refund_lamports)?;
+    }
+
+    Ok(())
+}
+
+//  rent settled by resizing an account from `old_len` to `new_len` bytes, as (top up, refund)
+//  lamports held beyond the old rent, e.g. curve reserves, are never part of it
+pub fn calc_resize_rent(rent: &Rent, old_len: usize, new_len: usize) -> (u64, u64) {
+    let old_rent = rent.minimum_balance(old_len);
+    let new_rent = rent.minimum_balance(new_len);
+
+    (new_rent.saturating_sub(old_rent), old_rent.saturating_sub(new_rent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_rent_difference_changes_hands() {
+        let rent = Rent::default();
+        let rent_difference = rent.minimum_balance(200) - rent.minimum_balance(100);
+
+        assert_eq!(calc_resize_rent(&rent, 100, 200), (rent_difference, 0));
+        assert_eq!(calc_resize_rent(&rent, 200, 100), (0, rent_difference));
+        assert_eq!(calc_resize_rent(&rent, 100, 100), (0, 0));
+    }
+}
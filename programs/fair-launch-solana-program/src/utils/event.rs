@@ -0,0 +1,35 @@
+use crate::constants::EVENT_AUTHORITY_SEED_IN_BYTES;
+use anchor_lang::{
+    event::EVENT_IX_TAG_LE,
+    Event,
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    },
+};
+
+//  same self-CPI as `emit_cpi!`, usable from handlers and state methods that have no `Context` in scope,
+//  the event ends up in the inner instructions instead of the program logs
+pub fn emit_cpi_event<E: Event>(
+    event_authority: &AccountInfo,
+    event_authority_bump: u8,
+    event: &E,
+) -> Result<()> {
+    let ix_data: Vec<u8> = EVENT_IX_TAG_LE
+        .into_iter()
+        .chain(event.data())
+        .collect();
+    let ix = Instruction::new_with_bytes(
+        crate::ID,
+        &ix_data,
+        vec![AccountMeta::new_readonly(event_authority.key(), true)],
+    );
+    invoke_signed(
+        &ix,
+        std::slice::from_ref(event_authority),
+        &[&[EVENT_AUTHORITY_SEED_IN_BYTES, &[event_authority_bump]]],
+    )?;
+
+    Ok(())
+}
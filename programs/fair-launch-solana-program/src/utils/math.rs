@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use crate::constants::BPS_DENOMINATOR;
+use crate::errors::CustomError;
+
+//  a * b / denominator, rounded down
+pub fn mul_div_floor(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    require!(denominator != 0, CustomError::OverflowOrUnderflowOccurred);
+
+    let product = a
+        .checked_mul(b)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    Ok(product / denominator)
+}
+
+//  a * b / denominator, rounded up
+pub fn mul_div_ceil(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    require!(denominator != 0, CustomError::OverflowOrUnderflowOccurred);
+
+    let product = a
+        .checked_mul(b)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred)?;
+
+    product
+        .checked_add(denominator - 1)
+        .map(|value| value / denominator)
+        .ok_or(CustomError::OverflowOrUnderflowOccurred.into())
+}
+
+//  fee charged on `amount`, rounded up so dust always stays with the protocol
+pub fn fee_ceil(amount: u64, fee_bps: u16) -> Result<u64> {
+    to_u64(mul_div_ceil(amount as u128, fee_bps as u128, BPS_DENOMINATOR as u128)?)
+}
+
+//  smallest gross amount that still leaves `amount_after_fee` once the fee is deducted
+pub fn amount_before_fee(amount_after_fee: u64, fee_bps: u16) -> Result<u64> {
+    require!((fee_bps as u64) < BPS_DENOMINATOR, CustomError::IncorrectValue);
+
+    to_u64(mul_div_ceil(
+        amount_after_fee as u128,
+        BPS_DENOMINATOR as u128,
+        (BPS_DENOMINATOR - fee_bps as u64) as u128,
+    )?)
+}
+
+//  integer square root, rounded down
+pub fn sqrt_floor(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    //  Newton's method, starting above the root so it converges from above
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+
+    x
+}
+
+//  convert a legacy f64 fee percentage into basis points
+pub fn percent_to_bps(fee_percent: f64) -> Result<u16> {
+    require!(
+        fee_percent.is_finite() && (0.0..=100.0).contains(&fee_percent),
+        CustomError::IncorrectValue
+    );
+
+    Ok((fee_percent * 100.0).round() as u16)
+}
+
+pub fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(CustomError::OverflowOrUnderflowOccurred))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_floor_rounds_down() {
+        assert_eq!(mul_div_floor(0, 7, 3).unwrap(), 0);
+        assert_eq!(mul_div_floor(7, 0, 3).unwrap(), 0);
+        assert_eq!(mul_div_floor(10, 1, 3).unwrap(), 3);
+        assert_eq!(mul_div_floor(9, 1, 3).unwrap(), 3);
+        assert_eq!(
+            mul_div_floor(u64::MAX as u128, u64::MAX as u128, u64::MAX as u128).unwrap(),
+            u64::MAX as u128
+        );
+        assert!(mul_div_floor(1, 1, 0).is_err());
+        assert!(mul_div_floor(u128::MAX, 2, 1).is_err());
+    }
+
+    #[test]
+    fn mul_div_ceil_rounds_up() {
+        assert_eq!(mul_div_ceil(0, 7, 3).unwrap(), 0);
+        assert_eq!(mul_div_ceil(10, 1, 3).unwrap(), 4);
+        assert_eq!(mul_div_ceil(9, 1, 3).unwrap(), 3);
+        assert_eq!(
+            mul_div_ceil(u64::MAX as u128, u64::MAX as u128, u64::MAX as u128).unwrap(),
+            u64::MAX as u128
+        );
+        assert!(mul_div_ceil(1, 1, 0).is_err());
+        assert!(mul_div_ceil(u128::MAX, 2, 1).is_err());
+        //  the rounding term itself must not overflow
+        assert!(mul_div_ceil(u128::MAX, 1, 2).is_err());
+    }
+
+    #[test]
+    fn fee_ceil_bounds() {
+        assert_eq!(fee_ceil(0, 100).unwrap(), 0);
+        assert_eq!(fee_ceil(1_000_000, 0).unwrap(), 0);
+        assert_eq!(fee_ceil(u64::MAX, 0).unwrap(), 0);
+        assert_eq!(fee_ceil(1, 1).unwrap(), 1);
+        assert_eq!(fee_ceil(10_000, 9999).unwrap(), 9999);
+        assert_eq!(fee_ceil(10_001, 9999).unwrap(), 10_000);
+        assert_eq!(
+            fee_ceil(u64::MAX, 9999).unwrap(),
+            ((u64::MAX as u128 * 9999).div_ceil(10_000)) as u64
+        );
+    }
+
+    #[test]
+    fn amount_before_fee_bounds() {
+        assert_eq!(amount_before_fee(0, 100).unwrap(), 0);
+        assert_eq!(amount_before_fee(1_000, 0).unwrap(), 1_000);
+        assert_eq!(amount_before_fee(u64::MAX, 0).unwrap(), u64::MAX);
+        assert_eq!(amount_before_fee(1, 9999).unwrap(), 10_000);
+        assert!(amount_before_fee(u64::MAX, 9999).is_err());
+        assert!(amount_before_fee(1, 10_000).is_err());
+
+        //  deducting the fee from the gross amount leaves at least the requested net amount
+        for fee_bps in [0u16, 1, 100, 250, 9999] {
+            for amount in [1u64, 999, 1_000_000, 123_456_789] {
+                let gross = amount_before_fee(amount, fee_bps).unwrap();
+                assert!(gross - fee_ceil(gross, fee_bps).unwrap() >= amount);
+            }
+        }
+    }
+
+    #[test]
+    fn sqrt_floor_bounds() {
+        assert_eq!(sqrt_floor(0), 0);
+        assert_eq!(sqrt_floor(1), 1);
+        assert_eq!(sqrt_floor(2), 1);
+        assert_eq!(sqrt_floor(3), 1);
+        assert_eq!(sqrt_floor(4), 2);
+        assert_eq!(sqrt_floor(15), 3);
+        assert_eq!(sqrt_floor(16), 4);
+        assert_eq!(sqrt_floor(17), 4);
+        assert_eq!(sqrt_floor(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+        assert_eq!(sqrt_floor(u64::MAX as u128 * u64::MAX as u128 - 1), u64::MAX as u128 - 1);
+        assert_eq!(sqrt_floor(u128::MAX), u64::MAX as u128);
+    }
+}
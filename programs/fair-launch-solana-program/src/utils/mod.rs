@@ -1,2 +1,8 @@
 pub mod transfer;
 pub use transfer::*;
+pub mod math;
+pub use math::*;
+pub mod account;
+pub use account::*;
+pub mod event;
+pub use event::*;
@@ -5,7 +5,7 @@ use anchor_lang::{
         system_instruction::transfer,
     },
 };
-use anchor_spl::token;
+use anchor_spl::token_interface;
 
 //  transfer sol from user
 pub fn sol_transfer_from_user<'info>(
@@ -28,37 +28,42 @@ pub fn sol_transfer_from_user<'info>(
 }
 
 //  transfer token from PDA
+#[allow(clippy::too_many_arguments)]
 pub fn token_transfer_from_pda<'info>(
     from: &AccountInfo<'info>,
     authority: &AccountInfo<'info>,
     to: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
     token_program: &AccountInfo<'info>,
     signer_seeds: &[&[&[u8]]],
     amount: u64,
+    decimals: u8,
 ) -> Result<()> {
     let cpi_ctx: CpiContext<_> = CpiContext::new_with_signer(
         token_program.to_account_info(),
-        token::Transfer {
+        token_interface::TransferChecked {
             from: from.to_account_info(),
-            authority: authority.to_account_info(),
+            mint: mint.to_account_info(),
             to: to.to_account_info(),
+            authority: authority.to_account_info(),
         },
         signer_seeds,
     );
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
 
     Ok(())
 }
 
-pub fn sol_transfer_from_curve<'info>(
-    bonding_curve_account: &mut AccountInfo<'info>,
-    user_account: &mut AccountInfo<'info>,
+//  transfer sol from a program owned PDA
+pub fn sol_transfer_from_pda<'info>(
+    pda_account: &mut AccountInfo<'info>,
+    destination: &mut AccountInfo<'info>,
     amount: u64,
 ) -> Result<()> {
-    **bonding_curve_account
+    **pda_account
         .to_account_info()
         .try_borrow_mut_lamports()? -= amount;
-    **user_account
+    **destination
         .to_account_info()
         .try_borrow_mut_lamports()? += amount;
 
@@ -69,17 +74,20 @@ pub fn token_transfer_from_user<'info>(
     from: &AccountInfo<'info>,
     authority: &AccountInfo<'info>,
     to: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
     token_program: &AccountInfo<'info>,
     amount: u64,
+    decimals: u8,
 ) -> Result<()> {
     let cpi_ctx: CpiContext<_> = CpiContext::new(
         token_program.to_account_info(),
-        token::Transfer {
+        token_interface::TransferChecked {
             from: from.to_account_info(),
-            authority: authority.to_account_info(),
+            mint: mint.to_account_info(),
             to: to.to_account_info(),
+            authority: authority.to_account_info(),
         },
     );
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
     Ok(())
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 import { web3 } from '@coral-xyz/anchor';
-import {feeRecipient, globalConfig, program} from "./constants";
+import {eventAuthority, feeRecipient, globalConfig, program} from "./constants";
 import {ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync, TOKEN_PROGRAM_ID} from "@solana/spl-token";
 import BN from "bn.js";
 import {getLocalAccount} from "./utils";
@@ -9,15 +9,27 @@ const deriveBondingCurve = (mint: web3.PublicKey) => web3.PublicKey.findProgramA
   program.programId
 );
 
-const buyToken = async (mintAddress: string, amount: number) => {
+const derivePriceOracle = (mint: web3.PublicKey) => web3.PublicKey.findProgramAddressSync(
+  [Buffer.from("price-oracle"), mint.toBuffer()],
+  program.programId
+);
+
+const deriveTraderStats = (trader: web3.PublicKey) => web3.PublicKey.findProgramAddressSync(
+  [Buffer.from("trader-stats"), trader.toBuffer()],
+  program.programId
+);
+
+const buyToken = async (mintAddress: string, amount: number, minAmountOut: number = 0) => {
   const tokenMint = new web3.PublicKey(mintAddress);
   const localUser = getLocalAccount();
   const [bondingCurve, _bondingCurveBump] = deriveBondingCurve(tokenMint);
+  const [priceOracle] = derivePriceOracle(tokenMint);
   const curveTokenAccount = getAssociatedTokenAddressSync(
     tokenMint,
     bondingCurve,
     true,
   );
+  const [traderStats] = deriveTraderStats(localUser.publicKey);
   const userTokenAccount = getAssociatedTokenAddressSync(
     tokenMint,
     localUser.publicKey,
@@ -27,6 +39,7 @@ const buyToken = async (mintAddress: string, amount: number) => {
     .swap(
       new BN(amount),
       true,
+      new BN(minAmountOut),
     )
     .accountsStrict({
       user: localUser.publicKey,
@@ -35,10 +48,15 @@ const buyToken = async (mintAddress: string, amount: number) => {
       globalConfig,
       tokenMint,
       bondingCurve,
+      priceOracle,
       curveTokenAccount,
+      referrer: null,
+      traderStats,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: web3.SystemProgram.programId,
+      eventAuthority,
+      program: program.programId,
     })
     .signers([localUser])
     .rpc();
@@ -22,3 +22,6 @@ export const feeRecipient = new anchor.web3.PublicKey("ABMHApyZu8DfuaGoKoLk4yRHF
 
 export const [globalConfig] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("global-config")], program.programId);
 
+
+//  signs the self-CPIs the program emits its events through
+export const [eventAuthority] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("__event_authority")], program.programId);
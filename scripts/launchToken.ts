@@ -1,5 +1,5 @@
 import { web3 } from '@coral-xyz/anchor';
-import {feeRecipient, globalConfig, program} from "./constants";
+import {eventAuthority, feeRecipient, globalConfig, program} from "./constants";
 import {ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync, TOKEN_PROGRAM_ID} from "@solana/spl-token";
 import BN from "bn.js";
 import {getLocalAccount} from "./utils";
@@ -11,6 +11,11 @@ const deriveBondingCurve = (mint: web3.PublicKey) => web3.PublicKey.findProgramA
   program.programId
 );
 
+const derivePriceOracle = (mint: web3.PublicKey) => web3.PublicKey.findProgramAddressSync(
+  [Buffer.from("price-oracle"), mint.toBuffer()],
+  program.programId
+);
+
 const launchToken = async (name: string, symbol: string, uri: string) => {
   const tokenMintKeypair = anchor.web3.Keypair.generate();
   const tokenMint = tokenMintKeypair.publicKey;
@@ -24,6 +29,7 @@ const launchToken = async (name: string, symbol: string, uri: string) => {
       new anchor.web3.PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID),
     );
   const [bondingCurve, _bondingCurveBump] = deriveBondingCurve(tokenMint);
+  const [priceOracle] = derivePriceOracle(tokenMint);
   const curveTokenAccount = getAssociatedTokenAddressSync(
     tokenMint,
     bondingCurve,
@@ -34,19 +40,33 @@ const launchToken = async (name: string, symbol: string, uri: string) => {
       name,
       symbol,
       uri,
+      null,
+      null,
+      null,
+      null,
+      null,
+      new BN(0),
     )
     .accountsStrict({
-      admin: localUser.publicKey,
+      creator: localUser.publicKey,
       globalConfig,
+      feeRecipient,
       tokenMint,
       bondingCurve,
+      priceOracle,
       curveTokenAccount,
+      vesting: null,
+      vestingTokenAccount: null,
+      creatorTokenAccount: null,
+      traderStats: null,
       tokenMetadataAccount,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
       rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       systemProgram: anchor.web3.SystemProgram.programId,
+      eventAuthority,
+      program: program.programId,
     })
     .signers([localUser, tokenMintKeypair])
     .rpc();
@@ -5,20 +5,166 @@ import BN from "bn.js";
 import {expect} from "chai";
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotent,
   getAccount,
   getAssociatedTokenAddressSync,
   getMint,
+  getTokenMetadata,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID
 } from "@solana/spl-token";
 import {MPL_TOKEN_METADATA_PROGRAM_ID} from "@metaplex-foundation/mpl-token-metadata"
 import {getLocalAccount} from "../scripts/utils";
-import {feeRecipient, globalConfig} from "../scripts/constants";
+import {eventAuthority, feeRecipient, globalConfig} from "../scripts/constants";
 
 describe("Happy Path", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
 
   const program = anchor.workspace.FairLaunchSolanaProgram as Program<FairLaunchSolanaProgram>;
+  //  spot prices are lamports per token unit, scaled by the program's PRICE_PRECISION
+  const pricePrecision = new BN(1_000_000_000);
+
+  //  the tests after the first two each launch a fresh token-2022 curve from the admin wallet
+  const launchToken = async (options: {
+    expiresAt?: BN,
+    vesting?: {beneficiary: anchor.web3.PublicKey, reservedBps: number, cliffSeconds: BN, durationSeconds: BN},
+    initialBuyLamports?: BN,
+    minAmountOut?: BN,
+  } = {}) => {
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+    const tokenMintKeypair = anchor.web3.Keypair.generate();
+    const tokenMint = tokenMintKeypair.publicKey;
+    const [bondingCurve] = anchor.web3.PublicKey.findProgramAddressSync([tokenMint.toBuffer()], program.programId);
+    const [priceOracle] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("price-oracle"), tokenMint.toBuffer()],
+      program.programId
+    );
+    const [vesting] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), tokenMint.toBuffer()],
+      program.programId
+    );
+    const curveTokenAccount = getAssociatedTokenAddressSync(tokenMint, bondingCurve, true, TOKEN_2022_PROGRAM_ID);
+    const vestingTokenAccount = getAssociatedTokenAddressSync(tokenMint, vesting, true, TOKEN_2022_PROGRAM_ID);
+    const creatorTokenAccount = getAssociatedTokenAddressSync(tokenMint, admin, false, TOKEN_2022_PROGRAM_ID);
+    const [creatorTraderStats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader-stats"), admin.toBuffer()],
+      program.programId
+    );
+    const launchHash = await program.methods
+      .launch(
+        "aTokenName",
+        "aTOKSYM",
+        "https://aTokenUri",
+        options.expiresAt ?? null,
+        null,
+        null,
+        options.vesting ?? null,
+        options.initialBuyLamports ?? null,
+        options.minAmountOut ?? new BN(0),
+      )
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        creator: admin,
+        globalConfig,
+        feeRecipient,
+        tokenMint,
+        bondingCurve,
+        priceOracle,
+        curveTokenAccount,
+        vesting: options.vesting ? vesting : null,
+        vestingTokenAccount: options.vesting ? vestingTokenAccount : null,
+        creatorTokenAccount: options.initialBuyLamports ? creatorTokenAccount : null,
+        traderStats: options.initialBuyLamports ? creatorTraderStats : null,
+        tokenMetadataAccount: null,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        metadataProgram: null,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([tokenMintKeypair, adminKeypair])
+      .rpc({commitment: "confirmed"});
+
+    return {
+      launchHash,
+      tokenMint,
+      bondingCurve,
+      priceOracle,
+      curveTokenAccount,
+      vesting,
+      vestingTokenAccount,
+      creatorTokenAccount,
+      creatorTraderStats,
+    };
+  };
+  type LaunchedToken = Awaited<ReturnType<typeof launchToken>>;
+
+  const swapAccounts = (token: LaunchedToken, user: anchor.web3.PublicKey) => ({
+    eventAuthority,
+    program: program.programId,
+    user,
+    feeRecipient,
+    userTokenAccount: getAssociatedTokenAddressSync(token.tokenMint, user, false, TOKEN_2022_PROGRAM_ID),
+    globalConfig,
+    tokenMint: token.tokenMint,
+    bondingCurve: token.bondingCurve,
+    curveTokenAccount: token.curveTokenAccount,
+    referrer: null,
+    traderStats: anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader-stats"), user.toBuffer()],
+      program.programId
+    )[0],
+    priceOracle: token.priceOracle,
+    tokenProgram: TOKEN_2022_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  //  events are emitted through self-CPIs, so they are read back from the inner instructions
+  const fetchEvents = async (txHash: string) => {
+    const tx = await anchor.getProvider().connection
+      .getTransaction(txHash, {commitment: "confirmed", maxSupportedTransactionVersion: 0});
+    const events = tx.meta.innerInstructions
+      .flatMap((innerInstruction) => innerInstruction.instructions)
+      .filter((instruction) => tx.transaction.message.staticAccountKeys[instruction.programIdIndex].equals(program.programId))
+      .map((instruction) => program.coder.events.decode(
+        anchor.utils.bytes.base64.encode(Buffer.from(anchor.utils.bytes.bs58.decode(instruction.data)).subarray(8))
+      ));
+    return {tx, events};
+  };
+
+  const sqrtFloor = (value: BN) => {
+    if (value.ltn(2)) {
+      return value;
+    }
+    let x = value;
+    let y = x.addn(1).divn(2);
+    while (y.lt(x)) {
+      x = y;
+      y = x.add(value.div(x)).divn(2);
+    }
+    return x;
+  };
+
+  const divCeil = (numerator: BN, denominator: BN) => numerator.add(denominator.subn(1)).div(denominator);
+
+  //  waits for the cluster clock, which curve expiry and vesting are measured against
+  const waitForClusterTime = async (timestamp: number) => {
+    const connection = anchor.getProvider().connection;
+    while ((await connection.getBlockTime(await connection.getSlot("confirmed"))) <= timestamp) {
+      await new Promise((resolve) => setTimeout(resolve, 1_000));
+    }
+  };
+
+  const fundedKeypair = async (lamports: number = anchor.web3.LAMPORTS_PER_SOL) => {
+    const connection = anchor.getProvider().connection;
+    const keypair = anchor.web3.Keypair.generate();
+    await connection.confirmTransaction(await connection.requestAirdrop(keypair.publicKey, lamports), "confirmed");
+    return keypair;
+  };
 
   it("Admin configures program, launches token, buys & sells the token", async () => {
     const connection = anchor.getProvider().connection;
@@ -26,16 +172,35 @@ describe("Happy Path", () => {
     const admin = anchor.getProvider().publicKey;
     const lamportsNeededToCompleteCurve = new BN(100 * anchor.web3.LAMPORTS_PER_SOL);
     const totalTokenSupply = new BN(1_000_000_000).mul((new BN(10).pow(new BN(6))));
-    const buyFeePercent = 1;
-    const sellFeePercent = 1;
+    const buyFeeBps = 100;
+    const sellFeeBps = 100;
+    const launchFeeLamports = new BN(0.01 * anchor.web3.LAMPORTS_PER_SOL);
+    const adminOnlyLaunch = false;
+    const initialVirtualLamportReserves = new BN(100 * anchor.web3.LAMPORTS_PER_SOL);
+    const initialVirtualTokenReserves = totalTokenSupply;
+    const maxReservedBps = 1_000;
+    const minVestingDurationSeconds = new BN(1);
     await program.methods
-      .configure({
+      .initializeConfig(
         feeRecipient,
-        lamportsNeededToCompleteCurve,
-        totalTokenSupply,
-        buyFeePercent,
-        sellFeePercent,
-      })
+        feeRecipient,
+        buyFeeBps,
+        sellFeeBps,
+        launchFeeLamports,
+        adminOnlyLaunch,
+        {
+          lamportsNeededToCompleteCurve,
+          totalTokenSupply,
+          initialVirtualLamportReserves,
+          initialVirtualTokenReserves,
+          minVirtualLamportReserves: initialVirtualLamportReserves.divn(2),
+          maxVirtualLamportReserves: initialVirtualLamportReserves.muln(2),
+          minVirtualTokenReserves: initialVirtualTokenReserves.divn(2),
+          maxVirtualTokenReserves: initialVirtualTokenReserves,
+          maxReservedBps,
+          minVestingDurationSeconds,
+        },
+      )
       .accountsStrict({
         admin,
         globalConfig,
@@ -44,32 +209,51 @@ describe("Happy Path", () => {
       .rpc();
 
     const globalConfigAccountData = await program.account.config.fetch(globalConfig);
+    expect(globalConfigAccountData.version).to.eql(1);
+    expect(globalConfigAccountData.authority).to.eql(admin);
     expect(globalConfigAccountData.feeRecipient).to.eql(feeRecipient);
+    expect(globalConfigAccountData.withdrawDestination).to.eql(feeRecipient);
     expect(globalConfigAccountData.lamportsNeededToCompleteCurve.toNumber()).to.eql(lamportsNeededToCompleteCurve.toNumber());
     expect(globalConfigAccountData.totalTokenSupply.toNumber()).to.eql(totalTokenSupply.toNumber());
-    expect(globalConfigAccountData.buyFeePercent).to.eql(buyFeePercent);
-    expect(globalConfigAccountData.sellFeePercent).to.eql(sellFeePercent);
+    expect(globalConfigAccountData.buyFeeBps).to.eql(buyFeeBps);
+    expect(globalConfigAccountData.sellFeeBps).to.eql(sellFeeBps);
+    expect(globalConfigAccountData.launchFeeLamports.toNumber()).to.eql(launchFeeLamports.toNumber());
+    expect(globalConfigAccountData.adminOnlyLaunch).to.eql(adminOnlyLaunch);
+    expect(globalConfigAccountData.initialVirtualLamportReserves.toString()).to.eql(initialVirtualLamportReserves.toString());
+    expect(globalConfigAccountData.initialVirtualTokenReserves.toString()).to.eql(initialVirtualTokenReserves.toString());
+    expect(globalConfigAccountData.maxReservedBps).to.eql(maxReservedBps);
+    expect(globalConfigAccountData.minVestingDurationSeconds.toNumber()).to.eql(minVestingDurationSeconds.toNumber());
 
     const randomKeypair = anchor.web3.Keypair.generate();
     await program.methods
-      .configure({
-        feeRecipient: admin,
-        lamportsNeededToCompleteCurve,
-        totalTokenSupply,
-        buyFeePercent,
-        sellFeePercent,
-      })
+      .updateFees(buyFeeBps, sellFeeBps, 0, 0, launchFeeLamports)
       .accountsStrict({
-        admin: randomKeypair.publicKey,
+        eventAuthority,
+        program: program.programId,
+        authority: randomKeypair.publicKey,
         globalConfig,
-        systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([randomKeypair])
       .rpc()
       .then(
-        () => Promise.reject(new Error("Should've thrown an address constraint error!")),
+        () => Promise.reject(new Error("Should've thrown a not authorized error!")),
         (e: AnchorError) => {
-          expect(e.errorLogs).includes('Program log: AnchorError caused by account: admin. Error Code: ConstraintAddress. Error Number: 2012. Error Message: An address constraint was violated.');
+          expect(e.error.errorCode.code).to.equal("NotAuthorized");
+        }
+      );
+    await program.methods
+      .updateFees(10_000, sellFeeBps, 0, 0, launchFeeLamports)
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        authority: admin,
+        globalConfig,
+      })
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an incorrect value error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("IncorrectValue");
         }
       );
 
@@ -88,19 +272,32 @@ describe("Happy Path", () => {
       );
     // const tokenMetadataAccount = anchor.web3.Keypair.generate().publicKey;
     const [bondingCurve] = anchor.web3.PublicKey.findProgramAddressSync([tokenMint.toBuffer()], program.programId);
+    const [priceOracle] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("price-oracle"), tokenMint.toBuffer()],
+      program.programId
+    );
     const curveTokenAccount = getAssociatedTokenAddressSync(
       tokenMint,
       bondingCurve,
       true
     );
+    const feeRecipientBalanceBeforeLaunchTx = await connection.getBalance(feeRecipient);
     const launchHash = await program.methods
-      .launch(tokenName, tokenSymbol, tokenUri)
+      .launch(tokenName, tokenSymbol, tokenUri, null, null, null, null, null, new BN(0))
       .accountsStrict({
-        admin,
+        eventAuthority,
+        program: program.programId,
+        creator: admin,
         globalConfig,
+        feeRecipient,
         tokenMint,
         bondingCurve,
+        priceOracle,
         curveTokenAccount,
+        vesting: null,
+        vestingTokenAccount: null,
+        creatorTokenAccount: null,
+        traderStats: null,
         tokenMetadataAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -111,6 +308,7 @@ describe("Happy Path", () => {
       .signers([tokenMintKeypair, adminKeypair])
       .rpc();
     console.log(`Token launched at tx: ${launchHash}, token mint account: ${tokenMint.toString()}`)
+    expect(await connection.getBalance(feeRecipient)).to.equal(feeRecipientBalanceBeforeLaunchTx + launchFeeLamports.toNumber());
 
     const tokenMintAccountInfo = await getMint(connection, tokenMint);
     expect(tokenMintAccountInfo.isInitialized).to.be.true
@@ -118,15 +316,32 @@ describe("Happy Path", () => {
     expect(tokenMintAccountInfo.supply.toString()).to.equal(totalTokenSupply.toString());
 
     const userTokenAccount = getAssociatedTokenAddressSync(tokenMint, admin);
+    const [traderStats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader-stats"), admin.toBuffer()],
+      program.programId
+    );
 
     const feeRecipientBalanceBeforeBuyTx = await connection.getBalance(feeRecipient);
     const buyAmountInLamports = 0.0001 * anchor.web3.LAMPORTS_PER_SOL;
+    const buyQuote = await program.methods
+      .quote(new BN(buyAmountInLamports), true)
+      .accountsStrict({
+        globalConfig,
+        tokenMint,
+        bondingCurve,
+        traderStats: null,
+      })
+      .view();
+    expect(buyQuote.feeLamports.toNumber()).to.equal(buyAmountInLamports * 0.01);
     const buyHash = await program.methods
       .swap(
         new BN(buyAmountInLamports),
         true,
+        buyQuote.amountOut,
       )
       .accountsStrict({
+        eventAuthority,
+        program: program.programId,
         user: admin,
         feeRecipient,
         userTokenAccount,
@@ -134,6 +349,9 @@ describe("Happy Path", () => {
         tokenMint,
         bondingCurve,
         curveTokenAccount,
+        referrer: null,
+        traderStats,
+        priceOracle,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -143,15 +361,103 @@ describe("Happy Path", () => {
     console.log(`Buy tx: ${buyHash}`);
     const feeRecipientAfterBuyTx = await connection.getBalance(feeRecipient)
     expect(feeRecipientAfterBuyTx).to.equal(feeRecipientBalanceBeforeBuyTx + (buyAmountInLamports * 0.01));
+    const userTokenAccountInfoAfterBuy = await getAccount(connection, userTokenAccount, "confirmed");
+    expect(userTokenAccountInfoAfterBuy.amount.toString()).to.equal(buyQuote.amountOut.toString());
+
+    const exactTokensOut = new BN(1_000_000);
+    const buyExactOutHash = await program.methods
+      .swapExactOut(
+        exactTokensOut,
+        true,
+        new BN(buyAmountInLamports),
+      )
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        user: admin,
+        feeRecipient,
+        userTokenAccount,
+        globalConfig,
+        tokenMint,
+        bondingCurve,
+        curveTokenAccount,
+        referrer: null,
+        traderStats,
+        priceOracle,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([adminKeypair])
+      .rpc();
+    console.log(`Buy exact out tx: ${buyExactOutHash}`);
     const userTokenAccountInfo = await getAccount(connection, userTokenAccount, "confirmed");
-    console.log(`User has ${userTokenAccountInfo.amount} tokens`);
+    expect((userTokenAccountInfo.amount - userTokenAccountInfoAfterBuy.amount).toString()).to.equal(exactTokensOut.toString());
+    const swapAccounts = {
+      eventAuthority,
+      program: program.programId,
+      user: admin,
+      feeRecipient,
+      userTokenAccount,
+      globalConfig,
+      tokenMint,
+      bondingCurve,
+      curveTokenAccount,
+      referrer: null,
+      traderStats,
+      priceOracle,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    await program.methods
+      .swapExactOut(exactTokensOut, true, new BN(1))
+      .accountsStrict(swapAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an amount in too large error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("AmountInTooLarge");
+        }
+      );
+
+    //  the lamports out of an exact out sell are net of the sell fee
+    const exactLamportsOut = new BN(1_000);
+    await program.methods
+      .swapExactOut(exactLamportsOut, false, new BN(1))
+      .accountsStrict(swapAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an amount in too large error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("AmountInTooLarge");
+        }
+      );
+    const sellExactOutHash = await program.methods
+      .swapExactOut(exactLamportsOut, false, new BN(userTokenAccountInfo.amount.toString()))
+      .accountsStrict(swapAccounts)
+      .signers([adminKeypair])
+      .rpc({commitment: "confirmed"});
+    console.log(`Sell exact out tx: ${sellExactOutHash}`);
+    const sellExactOutTx = await connection.getTransaction(sellExactOutHash, {commitment: "confirmed", maxSupportedTransactionVersion: 0});
+    //  the admin pays the transaction fee, so it is added back to their balance change
+    expect(sellExactOutTx.meta.postBalances[0] - sellExactOutTx.meta.preBalances[0] + sellExactOutTx.meta.fee)
+      .to.equal(exactLamportsOut.toNumber());
+
+    const userTokenAccountInfoAfterSell = await getAccount(connection, userTokenAccount, "confirmed");
+    expect(userTokenAccountInfoAfterSell.amount < userTokenAccountInfo.amount).to.be.true;
+    console.log(`User has ${userTokenAccountInfoAfterSell.amount} tokens`);
     const sellHash = await program.methods
       .swap(
-        // new BN(1979),
-        new BN(userTokenAccountInfo.amount.toString()),
+        new BN(userTokenAccountInfoAfterSell.amount.toString()),
         false,
+        new BN(0),
       )
       .accountsStrict({
+        eventAuthority,
+        program: program.programId,
         user: admin,
         feeRecipient,
         userTokenAccount,
@@ -159,6 +465,9 @@ describe("Happy Path", () => {
         tokenMint,
         bondingCurve,
         curveTokenAccount,
+        referrer: null,
+        traderStats,
+        priceOracle,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -167,4 +476,1204 @@ describe("Happy Path", () => {
       .rpc();
     console.log(`Sell tx: ${sellHash}`);
   });
+
+  it("Launches a token-2022 token with embedded metadata and buys it", async () => {
+    const connection = anchor.getProvider().connection;
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+
+    const tokenName = "aToken2022Name";
+    const tokenSymbol = "aT22SYM";
+    const tokenUri = "https://aToken2022Uri"
+    const tokenMintKeypair = anchor.web3.Keypair.generate();
+    const tokenMint = tokenMintKeypair.publicKey;
+    const [bondingCurve] = anchor.web3.PublicKey.findProgramAddressSync([tokenMint.toBuffer()], program.programId);
+    const [priceOracle] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("price-oracle"), tokenMint.toBuffer()],
+      program.programId
+    );
+    const curveTokenAccount = getAssociatedTokenAddressSync(
+      tokenMint,
+      bondingCurve,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const launchHash = await program.methods
+      .launch(tokenName, tokenSymbol, tokenUri, null, null, null, null, null, new BN(0))
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        creator: admin,
+        globalConfig,
+        feeRecipient,
+        tokenMint,
+        bondingCurve,
+        priceOracle,
+        curveTokenAccount,
+        vesting: null,
+        vestingTokenAccount: null,
+        creatorTokenAccount: null,
+        traderStats: null,
+        tokenMetadataAccount: null,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        metadataProgram: null,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([tokenMintKeypair, adminKeypair])
+      .rpc();
+    console.log(`Token-2022 token launched at tx: ${launchHash}, token mint account: ${tokenMint.toString()}`)
+
+    const tokenMetadata = await getTokenMetadata(connection, tokenMint, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(tokenMetadata.name).to.equal(tokenName);
+    expect(tokenMetadata.symbol).to.equal(tokenSymbol);
+    expect(tokenMetadata.uri).to.equal(tokenUri);
+
+    const userTokenAccount = getAssociatedTokenAddressSync(tokenMint, admin, false, TOKEN_2022_PROGRAM_ID);
+    const [traderStats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader-stats"), admin.toBuffer()],
+      program.programId
+    );
+    const creatorFeeBps = 50;
+    const referralFeeBps = 1_000;
+    await program.methods
+      .updateFees(100, 100, creatorFeeBps, referralFeeBps, new BN(0.01 * anchor.web3.LAMPORTS_PER_SOL))
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        authority: admin,
+        globalConfig,
+      })
+      .rpc();
+    const buyAmountInLamports = 0.0001 * anchor.web3.LAMPORTS_PER_SOL;
+    const buyQuote = await program.methods
+      .quote(new BN(buyAmountInLamports), true)
+      .accountsStrict({
+        globalConfig,
+        tokenMint,
+        bondingCurve,
+        traderStats: null,
+      })
+      .view();
+    const referrerKeypair = anchor.web3.Keypair.generate();
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(referrerKeypair.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+    const [referrer] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("referrer"), referrerKeypair.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerReferrer()
+      .accountsStrict({
+        wallet: referrerKeypair.publicKey,
+        referrer,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([referrerKeypair])
+      .rpc();
+    const swapAccounts = {
+      user: admin,
+      feeRecipient,
+      userTokenAccount,
+      globalConfig,
+      tokenMint,
+      bondingCurve,
+      curveTokenAccount,
+      referrer,
+      traderStats,
+      priceOracle,
+      eventAuthority,
+      program: program.programId,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    await program.methods
+      .setCurvePaused(true)
+      .accountsStrict({authority: admin, globalConfig, tokenMint, bondingCurve, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+    await program.methods
+      .swap(new BN(buyAmountInLamports), true, buyQuote.amountOut)
+      .accountsStrict(swapAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a trading paused error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("TradingPaused");
+        }
+      );
+    await program.methods
+      .setCurvePaused(false)
+      .accountsStrict({authority: admin, globalConfig, tokenMint, bondingCurve, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+
+    const buyHash = await program.methods
+      .swap(
+        new BN(buyAmountInLamports),
+        true,
+        buyQuote.amountOut,
+      )
+      .accountsStrict(swapAccounts)
+      .signers([adminKeypair])
+      .rpc();
+    console.log(`Token-2022 buy tx: ${buyHash}`);
+
+    const {tx: buyTx, events: buyEvents} = await fetchEvents(buyHash);
+    const tradeEvent = buyEvents.find((event) => event?.name === "tradeEvent");
+    const bondingCurveAccountData = await program.account.bondingCurve.fetch(bondingCurve);
+    const expectedCreatorFeeLamports = buyQuote.feeLamports.muln(creatorFeeBps).divn(100 + creatorFeeBps);
+    const expectedReferralFeeLamports = buyQuote.feeLamports.sub(expectedCreatorFeeLamports).muln(referralFeeBps).divn(10_000);
+    expect(tradeEvent.data.trader).to.eql(admin);
+    expect(tradeEvent.data.asset).to.eql(tokenMint);
+    expect(tradeEvent.data.isBuy).to.be.true;
+    expect(tradeEvent.data.sequence.toNumber()).to.equal(1);
+    expect(tradeEvent.data.amountIn.toString()).to.equal(buyQuote.amountIn.toString());
+    expect(tradeEvent.data.amountOut.toString()).to.equal(buyQuote.amountOut.toString());
+    expect(tradeEvent.data.referrer).to.eql(referrerKeypair.publicKey);
+    expect(tradeEvent.data.feeLamports.toString()).to.equal(buyQuote.feeLamports.toString());
+    expect(tradeEvent.data.creatorFeeLamports.toString()).to.equal(expectedCreatorFeeLamports.toString());
+    expect(tradeEvent.data.referralFeeLamports.toString()).to.equal(expectedReferralFeeLamports.toString());
+    //  reserves after the trade, as quoted and as stored on the curve
+    for (const reserves of [buyQuote, bondingCurveAccountData]) {
+      expect(tradeEvent.data.virtualLamportReserves.toString()).to.equal(reserves.virtualLamportReserves.toString());
+      expect(tradeEvent.data.virtualTokenReserves.toString()).to.equal(reserves.virtualTokenReserves.toString());
+      expect(tradeEvent.data.actualLamportReserves.toString()).to.equal(reserves.actualLamportReserves.toString());
+    }
+    expect(tradeEvent.data.spotPrice.toString()).to.equal(buyQuote.spotPrice.toString());
+    expect(tradeEvent.data.timestamp.toNumber()).to.equal(buyTx.blockTime);
+    expect(tradeEvent.data.slot.toNumber()).to.equal(buyTx.slot);
+    const userTokenAccountInfo = await getAccount(connection, userTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(userTokenAccountInfo.amount.toString()).to.equal(buyQuote.amountOut.toString());
+
+    //  the admin launched the token, so the creator fee part of the buy fee accrues to them
+    expect(bondingCurveAccountData.tradeCount.toNumber()).to.equal(1);
+
+    //  launch took the first price observation, the buy came too soon after it for a second one
+    const priceOracleAccountData = await program.account.priceOracle.fetch(priceOracle);
+    expect(priceOracleAccountData.mint).to.eql(tokenMint);
+    expect(priceOracleAccountData.observationCount).to.equal(1);
+    await program.methods
+      .twap(new BN(3600))
+      .accountsStrict({tokenMint, bondingCurve, priceOracle, pool: null})
+      .view()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a not enough price history error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("NotEnoughPriceHistory");
+        }
+      );
+    expect(bondingCurveAccountData.creatorFeeLamports.toString()).to.equal(expectedCreatorFeeLamports.toString());
+    const referrerAccountData = await program.account.referrer.fetch(referrer);
+    expect(referrerAccountData.accruedLamports.toString()).to.equal(expectedReferralFeeLamports.toString());
+
+    //  the admin is bound to the first referrer, a second wallet cannot take over its referral fees
+    expect((await program.account.traderStats.fetch(traderStats)).referrer).to.eql(referrerKeypair.publicKey);
+    const otherReferrerKeypair = await fundedKeypair();
+    const [otherReferrer] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("referrer"), otherReferrerKeypair.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerReferrer()
+      .accountsStrict({
+        wallet: otherReferrerKeypair.publicKey,
+        referrer: otherReferrer,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([otherReferrerKeypair])
+      .rpc();
+    await program.methods
+      .swap(new BN(buyAmountInLamports), true, new BN(0))
+      .accountsStrict({...swapAccounts, referrer: otherReferrer})
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a referrer mismatch error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("ReferrerMismatch");
+        }
+      );
+    await program.methods
+      .claimCreatorFees()
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        creator: admin,
+        tokenMint,
+        bondingCurve,
+      })
+      .rpc();
+    expect((await program.account.bondingCurve.fetch(bondingCurve)).creatorFeeLamports.toNumber()).to.equal(0);
+
+    //  the admin traded in both tests, so any positive threshold gives them the discounted fee
+    const traderStatsAccountData = await program.account.traderStats.fetch(traderStats);
+    expect(traderStatsAccountData.volumeLamports.toNumber()).to.be.greaterThan(0);
+    await program.methods
+      .updateFeeTiers([{minVolumeLamports: new BN(1), buyFeeBps: 50, sellFeeBps: 50}])
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .rpc();
+    const discountedQuote = await program.methods
+      .quote(new BN(buyAmountInLamports), true)
+      .accountsStrict({globalConfig, tokenMint, bondingCurve, traderStats})
+      .view();
+    expect(discountedQuote.feeLamports.toNumber()).to.equal(buyAmountInLamports * (50 + creatorFeeBps) / 10_000);
+    await program.methods
+      .updateFeeTiers([{minVolumeLamports: new BN(1), buyFeeBps: 200, sellFeeBps: 50}])
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an incorrect value error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("IncorrectValue");
+        }
+      );
+  });
+
+  it("Fills the buy that crosses the completion threshold only up to the threshold", async () => {
+    const connection = anchor.getProvider().connection;
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+
+    //  the remaining tests trade against curves that complete after 1 SOL
+    const lamportsNeededToCompleteCurve = new BN(anchor.web3.LAMPORTS_PER_SOL);
+    const config = await program.account.config.fetch(globalConfig);
+    await program.methods
+      .updateCurveParams({
+        lamportsNeededToCompleteCurve,
+        totalTokenSupply: config.totalTokenSupply,
+        initialVirtualLamportReserves: config.initialVirtualLamportReserves,
+        initialVirtualTokenReserves: config.initialVirtualTokenReserves,
+        minVirtualLamportReserves: config.minVirtualLamportReserves,
+        maxVirtualLamportReserves: config.maxVirtualLamportReserves,
+        minVirtualTokenReserves: config.minVirtualTokenReserves,
+        maxVirtualTokenReserves: config.maxVirtualTokenReserves,
+        maxReservedBps: config.maxReservedBps,
+        minVestingDurationSeconds: config.minVestingDurationSeconds,
+      })
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .rpc();
+
+    const token = await launchToken();
+    const accounts = swapAccounts(token, admin);
+    //  created upfront so the balance change of the buy is only the lamports taken by the curve
+    await createAssociatedTokenAccountIdempotent(
+      connection,
+      adminKeypair,
+      token.tokenMint,
+      admin,
+      {commitment: "confirmed"},
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const amountIn = new BN(2 * anchor.web3.LAMPORTS_PER_SOL);
+    const quote = await program.methods
+      .quote(amountIn, true)
+      .accountsStrict({
+        globalConfig,
+        tokenMint: token.tokenMint,
+        bondingCurve: token.bondingCurve,
+        traderStats: accounts.traderStats,
+      })
+      .view();
+    expect(quote.amountIn.lt(amountIn)).to.be.true;
+    expect(quote.actualLamportReserves.toString()).to.equal(lamportsNeededToCompleteCurve.toString());
+
+    //  the slippage limit only applies to the filled part of the amount in
+    await program.methods
+      .swap(amountIn, true, quote.amountOut.muln(2))
+      .accountsStrict(accounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a return amount too small error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("ReturnAmountTooSmall");
+        }
+      );
+    //  exact out buys are never filled partially
+    await program.methods
+      .swapExactOut(quote.amountOut.addn(1_000_000), true, amountIn)
+      .accountsStrict(accounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an amount out too large error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("AmountOutTooLarge");
+        }
+      );
+
+    const buyHash = await program.methods
+      .swap(amountIn, true, quote.amountOut)
+      .accountsStrict(accounts)
+      .signers([adminKeypair])
+      .rpc({commitment: "confirmed"});
+    console.log(`Completing buy tx: ${buyHash}`);
+
+    //  the unfilled part of the amount in never left the admin wallet
+    const buyTx = await connection.getTransaction(buyHash, {commitment: "confirmed", maxSupportedTransactionVersion: 0});
+    expect(buyTx.meta.preBalances[0] - buyTx.meta.postBalances[0] - buyTx.meta.fee).to.equal(quote.amountIn.toNumber());
+    const userTokenAccountInfo = await getAccount(connection, accounts.userTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(userTokenAccountInfo.amount.toString()).to.equal(quote.amountOut.toString());
+    const bondingCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(bondingCurveAccountData.isCompleted).to.be.true;
+    expect(bondingCurveAccountData.actualLamportReserves.toString()).to.equal(lamportsNeededToCompleteCurve.toString());
+  });
+
+  it("Migrates a completed curve into a pool and trades against it", async () => {
+    const connection = anchor.getProvider().connection;
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+
+    const token = await launchToken();
+    const accounts = swapAccounts(token, admin);
+    await program.methods
+      .swap(new BN(2 * anchor.web3.LAMPORTS_PER_SOL), true, new BN(0))
+      .accountsStrict(accounts)
+      .signers([adminKeypair])
+      .rpc();
+    const bondingCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(bondingCurveAccountData.isCompleted).to.be.true;
+    const curveTokenAccountInfo = await getAccount(connection, token.curveTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    const supplyBeforeMigrate = (await getMint(connection, token.tokenMint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply;
+
+    const [pool] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), token.tokenMint.toBuffer()],
+      program.programId
+    );
+    const [lpMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp-mint"), pool.toBuffer()],
+      program.programId
+    );
+    const poolTokenAccount = getAssociatedTokenAddressSync(token.tokenMint, pool, true, TOKEN_2022_PROGRAM_ID);
+    const userLpTokenAccount = getAssociatedTokenAddressSync(lpMint, admin, false, TOKEN_2022_PROGRAM_ID);
+    const migrateAccounts = {
+      eventAuthority,
+      program: program.programId,
+      authority: admin,
+      globalConfig,
+      tokenMint: token.tokenMint,
+      bondingCurve: token.bondingCurve,
+      priceOracle: token.priceOracle,
+      curveTokenAccount: token.curveTokenAccount,
+      pool,
+      lpMint,
+      poolTokenAccount,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    const randomKeypair = await fundedKeypair();
+    await program.methods
+      .migrate()
+      .accountsStrict({...migrateAccounts, authority: randomKeypair.publicKey})
+      .signers([randomKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a not authorized error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("NotAuthorized");
+        }
+      );
+    const migrateHash = await program.methods
+      .migrate()
+      .accountsStrict(migrateAccounts)
+      .signers([adminKeypair])
+      .rpc();
+    console.log(`Migrate tx: ${migrateHash}`);
+
+    //  the curve lamports move with the tokens matching them at the final curve price, the other curve tokens
+    //  are burned, the seed LP supply is never minted and stays locked
+    let poolAccountData = await program.account.pool.fetch(pool);
+    const expectedTokenReserves = bondingCurveAccountData.actualLamportReserves
+      .mul(bondingCurveAccountData.virtualTokenReserves)
+      .div(bondingCurveAccountData.virtualLamportReserves);
+    expect(poolAccountData.lamportReserves.toString()).to.equal(bondingCurveAccountData.actualLamportReserves.toString());
+    expect(poolAccountData.tokenReserves.toString()).to.equal(expectedTokenReserves.toString());
+    const tokensBurned = new BN(curveTokenAccountInfo.amount.toString()).sub(expectedTokenReserves);
+    const supplyAfterMigrate = (await getMint(connection, token.tokenMint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply;
+    expect((supplyBeforeMigrate - supplyAfterMigrate).toString()).to.equal(tokensBurned.toString());
+    expect((await getAccount(connection, token.curveTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString())
+      .to.equal("0");
+    expect(poolAccountData.lpSupply.toString())
+      .to.equal(sqrtFloor(poolAccountData.lamportReserves.mul(poolAccountData.tokenReserves)).toString());
+    const migratedCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(migratedCurveAccountData.isMigrated).to.be.true;
+    expect(migratedCurveAccountData.actualLamportReserves.toNumber()).to.equal(0);
+
+    const poolAccounts = {
+      eventAuthority,
+      program: program.programId,
+      user: admin,
+      globalConfig,
+      tokenMint: token.tokenMint,
+      pool,
+      priceOracle: token.priceOracle,
+      lpMint,
+      poolTokenAccount,
+      userTokenAccount: accounts.userTokenAccount,
+      userLpTokenAccount,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    //  the first deposit is priced against the locked seed supply, rounded up
+    const lpAmount = poolAccountData.lpSupply.divn(1_000);
+    const depositLamports = divCeil(poolAccountData.lamportReserves.mul(lpAmount), poolAccountData.lpSupply);
+    const depositTokens = divCeil(poolAccountData.tokenReserves.mul(lpAmount), poolAccountData.lpSupply);
+    await program.methods
+      .poolDeposit(lpAmount, depositLamports.subn(1), depositTokens)
+      .accountsStrict(poolAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an amount in too large error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("AmountInTooLarge");
+        }
+      );
+    await program.methods
+      .poolDeposit(lpAmount, depositLamports, depositTokens.subn(1))
+      .accountsStrict(poolAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an amount in too large error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("AmountInTooLarge");
+        }
+      );
+    await program.methods
+      .poolDeposit(lpAmount, depositLamports, depositTokens)
+      .accountsStrict(poolAccounts)
+      .signers([adminKeypair])
+      .rpc();
+    const userLpTokenAccountInfo = await getAccount(connection, userLpTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(userLpTokenAccountInfo.amount.toString()).to.equal(lpAmount.toString());
+    const poolAccountDataAfterDeposit = await program.account.pool.fetch(pool);
+    expect(poolAccountDataAfterDeposit.lamportReserves.toString()).to.equal(poolAccountData.lamportReserves.add(depositLamports).toString());
+    expect(poolAccountDataAfterDeposit.tokenReserves.toString()).to.equal(poolAccountData.tokenReserves.add(depositTokens).toString());
+    expect(poolAccountDataAfterDeposit.lpSupply.toString()).to.equal(poolAccountData.lpSupply.add(lpAmount).toString());
+    poolAccountData = poolAccountDataAfterDeposit;
+
+    //  the 30 bps pool fee stays in the reserves
+    const poolSwapAccounts = {
+      eventAuthority,
+      program: program.programId,
+      user: admin,
+      globalConfig,
+      tokenMint: token.tokenMint,
+      pool,
+      priceOracle: token.priceOracle,
+      poolTokenAccount,
+      userTokenAccount: accounts.userTokenAccount,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    const poolBuyLamports = new BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+    const poolBuyLamportsAfterFee = poolBuyLamports.sub(divCeil(poolBuyLamports.muln(30), new BN(10_000)));
+    const poolBuyTokensOut = poolAccountData.tokenReserves
+      .mul(poolBuyLamportsAfterFee)
+      .div(poolAccountData.lamportReserves.add(poolBuyLamportsAfterFee));
+    await program.methods
+      .poolSwap(poolBuyLamports, true, poolBuyTokensOut.addn(1))
+      .accountsStrict(poolSwapAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a return amount too small error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("ReturnAmountTooSmall");
+        }
+      );
+    //  the global pause halts pool trading as well
+    await program.methods
+      .setPaused(true)
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+    await program.methods
+      .poolSwap(poolBuyLamports, true, poolBuyTokensOut)
+      .accountsStrict(poolSwapAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a program paused error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("ProgramPaused");
+        }
+      );
+    await program.methods
+      .setPaused(false)
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+    const userTokenAccountInfoBeforePoolSwap = await getAccount(connection, accounts.userTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    await program.methods
+      .poolSwap(poolBuyLamports, true, poolBuyTokensOut)
+      .accountsStrict(poolSwapAccounts)
+      .signers([adminKeypair])
+      .rpc();
+    const userTokenAccountInfoAfterPoolSwap = await getAccount(connection, accounts.userTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect((userTokenAccountInfoAfterPoolSwap.amount - userTokenAccountInfoBeforePoolSwap.amount).toString())
+      .to.equal(poolBuyTokensOut.toString());
+    poolAccountData = await program.account.pool.fetch(pool);
+
+    //  the price history carries on after the migration, the pool prices the token from then on
+    await program.methods
+      .twap(new BN(1))
+      .accountsStrict({tokenMint: token.tokenMint, bondingCurve: token.bondingCurve, priceOracle: token.priceOracle, pool: null})
+      .view()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a pool required error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("PoolRequired");
+        }
+      );
+    const poolTwap = await program.methods
+      .twap(new BN(1))
+      .accountsStrict({tokenMint: token.tokenMint, bondingCurve: token.bondingCurve, priceOracle: token.priceOracle, pool})
+      .view();
+    expect(poolTwap.toNumber()).to.be.greaterThan(0);
+    expect(poolAccountData.lamportReserves.toString())
+      .to.equal(poolAccountDataAfterDeposit.lamportReserves.add(poolBuyLamports).toString());
+
+    //  withdrawing is priced against the LP supply as well, rounded down
+    const {globalConfig: _, ...poolWithdrawAccounts} = poolAccounts;
+    const withdrawLamports = poolAccountData.lamportReserves.mul(lpAmount).div(poolAccountData.lpSupply);
+    const withdrawTokens = poolAccountData.tokenReserves.mul(lpAmount).div(poolAccountData.lpSupply);
+    await program.methods
+      .poolWithdraw(lpAmount, withdrawLamports.addn(1), withdrawTokens)
+      .accountsStrict(poolWithdrawAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a return amount too small error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("ReturnAmountTooSmall");
+        }
+      );
+    //  the global pause halts trading, liquidity providers can still exit
+    await program.methods
+      .setPaused(true)
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+    await program.methods
+      .poolWithdraw(lpAmount, withdrawLamports, withdrawTokens)
+      .accountsStrict(poolWithdrawAccounts)
+      .signers([adminKeypair])
+      .rpc();
+    await program.methods
+      .setPaused(false)
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+    expect((await getAccount(connection, userLpTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString()).to.equal("0");
+    const poolAccountDataAfterWithdraw = await program.account.pool.fetch(pool);
+    expect(poolAccountDataAfterWithdraw.lpSupply.toString()).to.equal(poolAccountData.lpSupply.sub(lpAmount).toString());
+    expect(poolAccountDataAfterWithdraw.lamportReserves.toString())
+      .to.equal(poolAccountData.lamportReserves.sub(withdrawLamports).toString());
+    expect(poolAccountDataAfterWithdraw.tokenReserves.toString())
+      .to.equal(poolAccountData.tokenReserves.sub(withdrawTokens).toString());
+  });
+
+  it("Withdraws a completed curve to the configured destination instead of migrating it", async () => {
+    const connection = anchor.getProvider().connection;
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+
+    const token = await launchToken();
+    await program.methods
+      .swap(new BN(2 * anchor.web3.LAMPORTS_PER_SOL), true, new BN(0))
+      .accountsStrict(swapAccounts(token, admin))
+      .signers([adminKeypair])
+      .rpc();
+    const bondingCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(bondingCurveAccountData.isCompleted).to.be.true;
+    const curveTokenAccountInfo = await getAccount(connection, token.curveTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+
+    const withdrawAccounts = {
+      eventAuthority,
+      program: program.programId,
+      authority: admin,
+      globalConfig,
+      withdrawDestination: feeRecipient,
+      tokenMint: token.tokenMint,
+      bondingCurve: token.bondingCurve,
+      priceOracle: token.priceOracle,
+      curveTokenAccount: token.curveTokenAccount,
+      destinationTokenAccount: getAssociatedTokenAddressSync(token.tokenMint, feeRecipient, false, TOKEN_2022_PROGRAM_ID),
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    const randomKeypair = await fundedKeypair();
+    await program.methods
+      .withdrawCompleted()
+      .accountsStrict({...withdrawAccounts, authority: randomKeypair.publicKey})
+      .signers([randomKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a not authorized error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("NotAuthorized");
+        }
+      );
+    await program.methods
+      .withdrawCompleted()
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        ...withdrawAccounts,
+        withdrawDestination: randomKeypair.publicKey,
+        destinationTokenAccount: getAssociatedTokenAddressSync(token.tokenMint, randomKeypair.publicKey, false, TOKEN_2022_PROGRAM_ID),
+      })
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an incorrect withdraw destination error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("IncorrectWithdrawDestination");
+        }
+      );
+
+    const destinationBalanceBeforeWithdraw = await connection.getBalance(feeRecipient);
+    const withdrawHash = await program.methods
+      .withdrawCompleted()
+      .accountsStrict(withdrawAccounts)
+      .signers([adminKeypair])
+      .rpc();
+    console.log(`Withdraw tx: ${withdrawHash}`);
+    expect(await connection.getBalance(feeRecipient))
+      .to.equal(destinationBalanceBeforeWithdraw + bondingCurveAccountData.actualLamportReserves.toNumber());
+    const destinationTokenAccountInfo = await getAccount(connection, withdrawAccounts.destinationTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(destinationTokenAccountInfo.amount.toString()).to.equal(curveTokenAccountInfo.amount.toString());
+    const withdrawnCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(withdrawnCurveAccountData.isWithdrawn).to.be.true;
+    expect(withdrawnCurveAccountData.virtualTokenReserves.toNumber()).to.equal(0);
+    expect(withdrawnCurveAccountData.actualLamportReserves.toNumber()).to.equal(0);
+    await program.methods
+      .withdrawCompleted()
+      .accountsStrict(withdrawAccounts)
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a curve already withdrawn error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("CurveAlreadyWithdrawn");
+        }
+      );
+
+    //  a withdrawn curve can no longer be migrated
+    const [pool] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), token.tokenMint.toBuffer()],
+      program.programId
+    );
+    const [lpMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp-mint"), pool.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .migrate()
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        authority: admin,
+        globalConfig,
+        tokenMint: token.tokenMint,
+        bondingCurve: token.bondingCurve,
+        priceOracle: token.priceOracle,
+        curveTokenAccount: token.curveTokenAccount,
+        pool,
+        lpMint,
+        poolTokenAccount: getAssociatedTokenAddressSync(token.tokenMint, pool, true, TOKEN_2022_PROGRAM_ID),
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a curve already withdrawn error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("CurveAlreadyWithdrawn");
+        }
+      );
+  });
+
+  it("Refunds the holders of an expired curve out of its actual reserves", async () => {
+    const connection = anchor.getProvider().connection;
+
+    const expiresAt = Math.floor(Date.now() / 1_000) + 20;
+    const token = await launchToken({expiresAt: new BN(expiresAt)});
+    const holders = [await fundedKeypair(), await fundedKeypair()];
+    for (const [index, holder] of holders.entries()) {
+      await program.methods
+        .swap(new BN((index + 1) * 0.1 * anchor.web3.LAMPORTS_PER_SOL), true, new BN(0))
+        .accountsStrict(swapAccounts(token, holder.publicKey))
+        .signers([holder])
+        .rpc();
+    }
+    const bondingCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(bondingCurveAccountData.isCompleted).to.be.false;
+
+    await waitForClusterTime(expiresAt);
+
+    let refundedLamports = 0;
+    for (const holder of holders) {
+      const userTokenAccount = getAssociatedTokenAddressSync(token.tokenMint, holder.publicKey, false, TOKEN_2022_PROGRAM_ID);
+      const userTokenAccountInfo = await getAccount(connection, userTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      const refundHash = await program.methods
+        .claimRefund()
+        .accountsStrict({
+          eventAuthority,
+          program: program.programId,
+          user: holder.publicKey,
+          tokenMint: token.tokenMint,
+          bondingCurve: token.bondingCurve,
+          priceOracle: token.priceOracle,
+          curveTokenAccount: token.curveTokenAccount,
+          userTokenAccount,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([holder])
+        .rpc({commitment: "confirmed"});
+      console.log(`Refund tx: ${refundHash}`);
+
+      const refundTx = await connection.getTransaction(refundHash, {commitment: "confirmed", maxSupportedTransactionVersion: 0});
+      const refund = refundTx.meta.postBalances[0] - refundTx.meta.preBalances[0] + refundTx.meta.fee;
+      expect(refund).to.be.greaterThan(0);
+      refundedLamports += refund;
+      expect(userTokenAccountInfo.amount.toString()).to.not.equal("0");
+      expect((await getAccount(connection, userTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString()).to.equal("0");
+    }
+
+    //  refunds round down, so together they never exceed what the curve held
+    const actualLamportReserves = bondingCurveAccountData.actualLamportReserves.toNumber();
+    expect(refundedLamports).to.be.at.most(actualLamportReserves);
+    const refundedCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(refundedCurveAccountData.actualLamportReserves.toNumber()).to.equal(actualLamportReserves - refundedLamports);
+  });
+
+  it("Vests a reserved allocation linearly once the curve completes", async () => {
+    const connection = anchor.getProvider().connection;
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+
+    const beneficiary = await fundedKeypair();
+    const reservedBps = 1_000;
+    //  without an expiry a curve that never completes would lock the reserved allocation forever
+    const expiresAt = new BN(Math.floor(Date.now() / 1_000) + 3_600);
+    await launchToken({
+      vesting: {
+        beneficiary: beneficiary.publicKey,
+        reservedBps,
+        cliffSeconds: new BN(20),
+        durationSeconds: new BN(40),
+      },
+    }).then(
+      () => Promise.reject(new Error("Should've thrown a vesting requires expiry error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("VestingRequiresExpiry");
+      }
+    );
+    //  launches are permissionless, so the reserved share is capped by the config
+    await launchToken({
+      expiresAt,
+      vesting: {
+        beneficiary: beneficiary.publicKey,
+        reservedBps: reservedBps + 1,
+        cliffSeconds: new BN(20),
+        durationSeconds: new BN(40),
+      },
+    }).then(
+      () => Promise.reject(new Error("Should've thrown an incorrect value error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("IncorrectValue");
+      }
+    );
+    const token = await launchToken({
+      expiresAt,
+      vesting: {
+        beneficiary: beneficiary.publicKey,
+        reservedBps,
+        cliffSeconds: new BN(20),
+        durationSeconds: new BN(40),
+      },
+    });
+
+    const config = await program.account.config.fetch(globalConfig);
+    const vestingAccountData = await program.account.vesting.fetch(token.vesting);
+    const totalAmount = config.totalTokenSupply.muln(reservedBps).divn(10_000);
+    expect(vestingAccountData.beneficiary).to.eql(beneficiary.publicKey);
+    expect(vestingAccountData.totalAmount.toString()).to.equal(totalAmount.toString());
+    expect(vestingAccountData.claimedAmount.toNumber()).to.equal(0);
+    expect(vestingAccountData.cliffTs.sub(vestingAccountData.startTs).toNumber()).to.equal(20);
+    expect(vestingAccountData.endTs.sub(vestingAccountData.startTs).toNumber()).to.equal(40);
+    const vestingTokenAccountInfo = await getAccount(connection, token.vestingTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(vestingTokenAccountInfo.amount.toString()).to.equal(totalAmount.toString());
+    expect((await program.account.bondingCurve.fetch(token.bondingCurve)).reservedTokens.toString()).to.equal(totalAmount.toString());
+
+    const beneficiaryTokenAccount = getAssociatedTokenAddressSync(token.tokenMint, beneficiary.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const claimVested = () => program.methods
+      .claimVested()
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        beneficiary: beneficiary.publicKey,
+        tokenMint: token.tokenMint,
+        bondingCurve: token.bondingCurve,
+        vesting: token.vesting,
+        vestingTokenAccount: token.vestingTokenAccount,
+        beneficiaryTokenAccount,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([beneficiary])
+      .rpc({commitment: "confirmed"});
+
+    //  nothing unlocks while the curve is still trading
+    await claimVested().then(
+      () => Promise.reject(new Error("Should've thrown a curve not completed error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("CurveNotCompleted");
+      }
+    );
+    await program.methods
+      .swap(new BN(2 * anchor.web3.LAMPORTS_PER_SOL), true, new BN(0))
+      .accountsStrict(swapAccounts(token, admin))
+      .signers([adminKeypair])
+      .rpc();
+
+    //  before the cliff
+    await claimVested().then(
+      () => Promise.reject(new Error("Should've thrown a nothing to claim error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("NothingToClaim");
+      }
+    );
+
+    //  linear phase, measured from the launch rather than the cliff
+    await waitForClusterTime(vestingAccountData.cliffTs.toNumber());
+    await claimVested();
+    const claimedAfterCliff = (await getAccount(connection, beneficiaryTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString();
+    expect(new BN(claimedAfterCliff).gte(totalAmount.divn(2))).to.be.true;
+    expect(new BN(claimedAfterCliff).lt(totalAmount)).to.be.true;
+    expect((await program.account.vesting.fetch(token.vesting)).claimedAmount.toString()).to.equal(claimedAfterCliff);
+
+    //  after the end
+    await waitForClusterTime(vestingAccountData.endTs.toNumber());
+    await claimVested();
+    expect((await getAccount(connection, beneficiaryTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString())
+      .to.equal(totalAmount.toString());
+    expect((await getAccount(connection, token.vestingTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString())
+      .to.equal("0");
+    await claimVested().then(
+      () => Promise.reject(new Error("Should've thrown a nothing to claim error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("NothingToClaim");
+      }
+    );
+  });
+
+  it("Burns the reserved allocation of an expired curve", async () => {
+    const connection = anchor.getProvider().connection;
+    const admin = anchor.getProvider().publicKey;
+
+    const expiresAt = Math.floor(Date.now() / 1_000) + 20;
+    const token = await launchToken({
+      expiresAt: new BN(expiresAt),
+      vesting: {
+        beneficiary: admin,
+        reservedBps: 1_000,
+        cliffSeconds: new BN(0),
+        durationSeconds: new BN(1),
+      },
+    });
+    const reservedTokens = (await program.account.bondingCurve.fetch(token.bondingCurve)).reservedTokens;
+    const supplyBeforeBurn = (await getMint(connection, token.tokenMint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply;
+
+    const burnReservedTokens = () => program.methods
+      .burnReservedTokens()
+      .accountsStrict({
+        eventAuthority,
+        program: program.programId,
+        tokenMint: token.tokenMint,
+        bondingCurve: token.bondingCurve,
+        vesting: token.vesting,
+        vestingTokenAccount: token.vestingTokenAccount,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc({commitment: "confirmed"});
+    await burnReservedTokens().then(
+      () => Promise.reject(new Error("Should've thrown a curve not expired error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("CurveNotExpired");
+      }
+    );
+
+    await waitForClusterTime(expiresAt);
+    await burnReservedTokens();
+    const supplyAfterBurn = (await getMint(connection, token.tokenMint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply;
+    expect((supplyBeforeBurn - supplyAfterBurn).toString()).to.equal(reservedTokens.toString());
+    expect((await program.account.bondingCurve.fetch(token.bondingCurve)).reservedTokens.toNumber()).to.equal(0);
+    expect((await getAccount(connection, token.vestingTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString())
+      .to.equal("0");
+  });
+
+  it("Launches a token with a creator dev-buy", async () => {
+    const connection = anchor.getProvider().connection;
+
+    //  the dev-buy pays the buy fee of the creator's volume tier plus the creator fee
+    const config = await program.account.config.fetch(globalConfig);
+    const admin = anchor.getProvider().publicKey;
+    const [creatorTraderStats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader-stats"), admin.toBuffer()],
+      program.programId
+    );
+    const volumeLamportsBefore = (await program.account.traderStats.fetch(creatorTraderStats)).volumeLamports;
+    const feeTier = config.feeTiers
+      .slice(0, config.feeTierCount)
+      .reverse()
+      .find((feeTier) => volumeLamportsBefore.gte(feeTier.minVolumeLamports));
+    const buyFeeBps = feeTier ? feeTier.buyFeeBps : config.buyFeeBps;
+    const initialBuyLamports = new BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+    const feeLamports = divCeil(initialBuyLamports.muln(buyFeeBps + config.creatorFeeBps), new BN(10_000));
+    const lamportsIn = initialBuyLamports.sub(feeLamports);
+    const tokensOut = config.initialVirtualTokenReserves
+      .mul(lamportsIn)
+      .div(config.initialVirtualLamportReserves.add(lamportsIn));
+
+    await launchToken({initialBuyLamports, minAmountOut: tokensOut.addn(1)}).then(
+      () => Promise.reject(new Error("Should've thrown a return amount too small error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("ReturnAmountTooSmall");
+      }
+    );
+    const token = await launchToken({initialBuyLamports, minAmountOut: tokensOut});
+
+    const creatorTokenAccountInfo = await getAccount(connection, token.creatorTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(creatorTokenAccountInfo.amount.toString()).to.equal(tokensOut.toString());
+    const bondingCurveAccountData = await program.account.bondingCurve.fetch(token.bondingCurve);
+    expect(bondingCurveAccountData.tradeCount.toNumber()).to.equal(1);
+    expect((await program.account.traderStats.fetch(token.creatorTraderStats)).volumeLamports.toString())
+      .to.equal(volumeLamportsBefore.add(initialBuyLamports).toString());
+    expect(bondingCurveAccountData.actualLamportReserves.toString()).to.equal(lamportsIn.toString());
+    expect(bondingCurveAccountData.virtualLamportReserves.toString())
+      .to.equal(config.initialVirtualLamportReserves.add(lamportsIn).toString());
+    expect(bondingCurveAccountData.virtualTokenReserves.toString())
+      .to.equal(config.initialVirtualTokenReserves.sub(tokensOut).toString());
+    const curveTokenAccountInfo = await getAccount(connection, token.curveTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(curveTokenAccountInfo.amount.toString()).to.equal(config.totalTokenSupply.sub(tokensOut).toString());
+
+    const {tx: launchTx, events: launchEvents} = await fetchEvents(token.launchHash);
+    const tradeEvent = launchEvents.find((event) => event?.name === "tradeEvent");
+    expect(tradeEvent.data.trader).to.eql(admin);
+    expect(tradeEvent.data.asset).to.eql(token.tokenMint);
+    expect(tradeEvent.data.isBuy).to.be.true;
+    expect(tradeEvent.data.sequence.toNumber()).to.equal(1);
+    expect(tradeEvent.data.amountIn.toString()).to.equal(initialBuyLamports.toString());
+    expect(tradeEvent.data.amountOut.toString()).to.equal(tokensOut.toString());
+    expect(tradeEvent.data.referrer).to.be.null;
+    expect(tradeEvent.data.referralFeeLamports.toNumber()).to.equal(0);
+    expect(tradeEvent.data.feeLamports.toString()).to.equal(feeLamports.toString());
+    expect(tradeEvent.data.creatorFeeLamports.toString()).to.equal(bondingCurveAccountData.creatorFeeLamports.toString());
+    expect(tradeEvent.data.virtualLamportReserves.toString()).to.equal(bondingCurveAccountData.virtualLamportReserves.toString());
+    expect(tradeEvent.data.virtualTokenReserves.toString()).to.equal(bondingCurveAccountData.virtualTokenReserves.toString());
+    expect(tradeEvent.data.actualLamportReserves.toString()).to.equal(bondingCurveAccountData.actualLamportReserves.toString());
+    expect(tradeEvent.data.spotPrice.toString()).to.equal(
+      bondingCurveAccountData.virtualLamportReserves.mul(pricePrecision).div(bondingCurveAccountData.virtualTokenReserves).toString()
+    );
+    expect(tradeEvent.data.timestamp.toNumber()).to.equal(launchTx.blockTime);
+    expect(tradeEvent.data.slot.toNumber()).to.equal(launchTx.slot);
+  });
+
+  it("Splits the protocol fee of curve buys and sells across the fee shares", async () => {
+    const connection = anchor.getProvider().connection;
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+
+    //  without volume discounts every trade below pays the base fees
+    await program.methods
+      .updateFeeTiers([])
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+
+    const recipients = [(await fundedKeypair()).publicKey, (await fundedKeypair()).publicKey];
+    const feeShares = [
+      {recipient: recipients[0], weightBps: 5_000},
+      {recipient: recipients[1], weightBps: 2_500},
+    ];
+    const updateFeeShares = (shares: typeof feeShares, shareAccounts: anchor.web3.PublicKey[]) => program.methods
+      .updateFeeShares(shares)
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .remainingAccounts(shareAccounts.map((pubkey) => ({pubkey, isSigner: false, isWritable: false})))
+      .signers([adminKeypair])
+      .rpc({commitment: "confirmed"});
+
+    //  a small share paid to an unfunded recipient would fail the whole trade
+    const unfundedRecipient = anchor.web3.Keypair.generate().publicKey;
+    await updateFeeShares([{recipient: unfundedRecipient, weightBps: 5_000}], [unfundedRecipient]).then(
+      () => Promise.reject(new Error("Should've thrown a fee recipient not rent exempt error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("FeeRecipientNotRentExempt");
+      }
+    );
+    await updateFeeShares(feeShares, [recipients[0]]).then(
+      () => Promise.reject(new Error("Should've thrown an incorrect fee recipient error!")),
+      (e: AnchorError) => {
+        expect(e.error.errorCode.code).to.equal("IncorrectFeeRecipient");
+      }
+    );
+    await updateFeeShares(feeShares, recipients);
+
+    const token = await launchToken();
+    const trader = await fundedKeypair();
+    const accounts = swapAccounts(token, trader.publicKey);
+    const feeShareAccounts = recipients.map((pubkey) => ({pubkey, isSigner: false, isWritable: true}));
+    const config = await program.account.config.fetch(globalConfig);
+
+    //  the admin launched the token, so the creator part of the fee stays on the curve and the shares split the rest
+    const expectedBalanceChanges = (feeLamports: BN, feeBps: number) => {
+      const creatorFeeLamports = feeLamports.muln(config.creatorFeeBps).divn(feeBps + config.creatorFeeBps);
+      const protocolFeeLamports = feeLamports.sub(creatorFeeLamports);
+      const shareLamports = feeShares.map(({weightBps}) => protocolFeeLamports.muln(weightBps).divn(10_000));
+      const feeRecipientLamports = shareLamports.reduce((left, lamports) => left.sub(lamports), protocolFeeLamports);
+      return [...shareLamports, feeRecipientLamports].map((lamports) => lamports.toNumber());
+    };
+    const balances = () => Promise.all(
+      [...recipients, feeRecipient].map((pubkey) => connection.getBalance(pubkey, "confirmed"))
+    );
+    const balanceChanges = async (balancesBefore: number[]) =>
+      (await balances()).map((balance, index) => balance - balancesBefore[index]);
+
+    const buyLamports = new BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+    const buyQuote = await program.methods
+      .quote(buyLamports, true)
+      .accountsStrict({globalConfig, tokenMint: token.tokenMint, bondingCurve: token.bondingCurve, traderStats: null})
+      .view();
+    await program.methods
+      .swap(buyLamports, true, buyQuote.amountOut)
+      .accountsStrict(accounts)
+      .signers([trader])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown an incorrect fee recipient error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("IncorrectFeeRecipient");
+        }
+      );
+    const balancesBeforeBuy = await balances();
+    await program.methods
+      .swap(buyLamports, true, buyQuote.amountOut)
+      .accountsStrict(accounts)
+      .remainingAccounts(feeShareAccounts)
+      .signers([trader])
+      .rpc({commitment: "confirmed"});
+    expect(await balanceChanges(balancesBeforeBuy)).to.eql(expectedBalanceChanges(buyQuote.feeLamports, config.buyFeeBps));
+
+    const sellQuote = await program.methods
+      .quote(buyQuote.amountOut, false)
+      .accountsStrict({globalConfig, tokenMint: token.tokenMint, bondingCurve: token.bondingCurve, traderStats: accounts.traderStats})
+      .view();
+    const balancesBeforeSell = await balances();
+    const sellHash = await program.methods
+      .swap(buyQuote.amountOut, false, sellQuote.amountOut)
+      .accountsStrict(accounts)
+      .remainingAccounts(feeShareAccounts)
+      .signers([trader])
+      .rpc({commitment: "confirmed"});
+    expect(await balanceChanges(balancesBeforeSell)).to.eql(expectedBalanceChanges(sellQuote.feeLamports, config.sellFeeBps));
+    //  like the quote, the sell event reports the lamports the trader received after the fee
+    const sellEvent = (await fetchEvents(sellHash)).events.find((event) => event?.name === "tradeEvent");
+    expect(sellEvent.data.isBuy).to.be.false;
+    expect(sellEvent.data.amountIn.toString()).to.equal(buyQuote.amountOut.toString());
+    expect(sellEvent.data.amountOut.toString()).to.equal(sellQuote.amountOut.toString());
+    expect(sellEvent.data.feeLamports.toString()).to.equal(sellQuote.feeLamports.toString());
+
+    //  the remaining tests trade without fee shares
+    await updateFeeShares([], []);
+  });
+
+  it("Hands the authority over only to the proposed account", async () => {
+    const adminKeypair = await getLocalAccount();
+    const admin = anchor.getProvider().publicKey;
+
+    const newAuthority = await fundedKeypair();
+    const randomKeypair = await fundedKeypair();
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accountsStrict({authority: randomKeypair.publicKey, globalConfig, eventAuthority, program: program.programId})
+      .signers([randomKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a not authorized error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("NotAuthorized");
+        }
+      );
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+    let globalConfigAccountData = await program.account.config.fetch(globalConfig);
+    expect(globalConfigAccountData.authority).to.eql(admin);
+    expect(globalConfigAccountData.pendingAuthority).to.eql(newAuthority.publicKey);
+
+    await program.methods
+      .acceptAuthority()
+      .accountsStrict({newAuthority: randomKeypair.publicKey, globalConfig, eventAuthority, program: program.programId})
+      .signers([randomKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a not authorized error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("NotAuthorized");
+        }
+      );
+    await program.methods
+      .acceptAuthority()
+      .accountsStrict({newAuthority: newAuthority.publicKey, globalConfig, eventAuthority, program: program.programId})
+      .signers([newAuthority])
+      .rpc();
+    globalConfigAccountData = await program.account.config.fetch(globalConfig);
+    expect(globalConfigAccountData.authority).to.eql(newAuthority.publicKey);
+    expect(globalConfigAccountData.pendingAuthority).to.eql(anchor.web3.PublicKey.default);
+
+    //  the previous authority lost its rights, the new one hands them back for the remaining tests
+    await program.methods
+      .setPaused(true)
+      .accountsStrict({authority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc()
+      .then(
+        () => Promise.reject(new Error("Should've thrown a not authorized error!")),
+        (e: AnchorError) => {
+          expect(e.error.errorCode.code).to.equal("NotAuthorized");
+        }
+      );
+    await program.methods
+      .proposeAuthority(admin)
+      .accountsStrict({authority: newAuthority.publicKey, globalConfig, eventAuthority, program: program.programId})
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accountsStrict({newAuthority: admin, globalConfig, eventAuthority, program: program.programId})
+      .signers([adminKeypair])
+      .rpc();
+    expect((await program.account.config.fetch(globalConfig)).authority).to.eql(admin);
+  });
 });